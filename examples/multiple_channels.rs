@@ -242,7 +242,7 @@ impl<T> Default for ChannelAudioState<T> {
             stopped: true,
             loop_started: false,
             paused: false,
-            _marker: PhantomData::<T>::default(),
+            _marker: PhantomData::<T>,
         }
     }
 }
@@ -355,7 +355,7 @@ fn build_button_row<T: Component + Default + Clone>(
                 NORMAL_BUTTON.into(),
                 ChangeVolumeButton::<T> {
                     louder: true,
-                    _marker: PhantomData::default(),
+                    _marker: PhantomData,
                 },
                 font.clone(),
             );
@@ -365,7 +365,7 @@ fn build_button_row<T: Component + Default + Clone>(
                 NORMAL_BUTTON.into(),
                 ChangeVolumeButton::<T> {
                     louder: false,
-                    _marker: PhantomData::default(),
+                    _marker: PhantomData,
                 },
                 font.clone(),
             );
@@ -11,4 +11,6 @@ fn main() {
 
 fn play_audio(asset_server: Res<AssetServer>, audio: Res<Audio>) {
     audio.play(asset_server.load("sounds/loop_with_settings.ogg.ron"));
+    // Every play of a group picks one of its sounds
+    audio.play(asset_server.load("sounds/plops.group.ron"));
 }
@@ -16,6 +16,7 @@ fn main() {
         .insert_resource(AudioSettings {
             sound_capacity: 4096,
             command_capacity: 1024,
+            ..default()
         })
         .add_plugin(AudioPlugin)
         .add_startup_system(prepare)
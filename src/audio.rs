@@ -1,22 +1,105 @@
-use crate::audio_output::{play_audio_channel, update_instance_states, InstanceState};
+use crate::audio_output::{play_audio_channel, update_instance_states, AudioOutput, InstanceState};
+use crate::effects::{AudioEffect, EffectParameter};
+use crate::layers::{LayeredInstance, LayeredMusic};
+use crate::play::{PlayAudioCommand, PlayAudioSettings};
+use crate::playlist::{update_playlist, Playlist};
+use crate::quantize::{Quantize, QuantizeReference, QuantizedStart};
+use crate::sequence::{update_music_sequencer, MusicSequencer};
+use crate::settings::{InstanceLimit, PlayCooldown};
 use crate::source::AudioSource;
+use crate::tween::{AudioTween, TweenCommand};
 use crate::{AudioSystemLabel, ParallelSystemDescriptorCoercion};
-use bevy::app::{App, CoreStage};
-use bevy::asset::Handle;
-use bevy::ecs::system::Resource;
+use bevy::app::{App, CoreStage, StartupStage};
+use bevy::asset::{Handle, HandleId};
+use bevy::ecs::system::{NonSendMut, Resource};
+use bevy::log::warn;
 use parking_lot::RwLock;
 use std::collections::{HashMap, VecDeque};
 use std::marker::PhantomData;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
 pub(crate) enum AudioCommand {
-    Play(PlayAudioCommandArgs),
-    SetVolume(f32),
-    SetPanning(f32),
-    SetPlaybackRate(f32),
-    Stop,
-    Pause,
-    Resume,
+    Play(Box<PlayAudioCommandArgs>),
+    /// Play all layers starting on the same sample
+    PlayLayered(Vec<PlayAudioCommandArgs>),
+    SetVolume(f32, AudioTween),
+    SetPanning(f32, AudioTween),
+    SetPlaybackRate(f32, AudioTween),
+    Stop(AudioTween),
+    Pause(AudioTween),
+    Resume(AudioTween),
+    StopInstance(InstanceHandle, AudioTween),
+    PauseInstance(InstanceHandle, AudioTween),
+    ResumeInstance(InstanceHandle, AudioTween),
+    SetInstanceVolume(InstanceHandle, f32, AudioTween),
+    SetInstancePanning(InstanceHandle, f32, AudioTween),
+    SetInstancePlaybackRate(InstanceHandle, f32, AudioTween),
+    SeekTo(InstanceHandle, f64),
+    SeekBy(InstanceHandle, f64),
+    DisableLooping(InstanceHandle),
+    SetEffectParameter(usize, EffectParameter, AudioTween),
+}
+
+impl AudioCommand {
+    /// The instance this command is targeting, if it only applies to a single instance
+    pub(crate) fn target_instance(&self) -> Option<&InstanceHandle> {
+        match self {
+            AudioCommand::StopInstance(handle, _)
+            | AudioCommand::PauseInstance(handle, _)
+            | AudioCommand::ResumeInstance(handle, _)
+            | AudioCommand::SetInstanceVolume(handle, _, _)
+            | AudioCommand::SetInstancePanning(handle, _, _)
+            | AudioCommand::SetInstancePlaybackRate(handle, _, _)
+            | AudioCommand::SeekTo(handle, _)
+            | AudioCommand::SeekBy(handle, _)
+            | AudioCommand::DisableLooping(handle) => Some(handle),
+            _ => None,
+        }
+    }
+
+    /// The tween of this command, if it supports one
+    pub(crate) fn tween_mut(&mut self) -> Option<&mut AudioTween> {
+        match self {
+            AudioCommand::SetVolume(_, tween)
+            | AudioCommand::SetPanning(_, tween)
+            | AudioCommand::SetPlaybackRate(_, tween)
+            | AudioCommand::Stop(tween)
+            | AudioCommand::Pause(tween)
+            | AudioCommand::Resume(tween)
+            | AudioCommand::StopInstance(_, tween)
+            | AudioCommand::PauseInstance(_, tween)
+            | AudioCommand::ResumeInstance(_, tween)
+            | AudioCommand::SetInstanceVolume(_, _, tween)
+            | AudioCommand::SetInstancePanning(_, _, tween)
+            | AudioCommand::SetInstancePlaybackRate(_, _, tween)
+            | AudioCommand::SetEffectParameter(_, _, tween) => Some(tween),
+            AudioCommand::Play(_)
+            | AudioCommand::PlayLayered(_)
+            | AudioCommand::SeekTo(_, _)
+            | AudioCommand::SeekBy(_, _)
+            | AudioCommand::DisableLooping(_) => None,
+        }
+    }
+
+    /// Time to wait before running this command
+    pub(crate) fn start_delay_mut(&mut self) -> Option<&mut Duration> {
+        match self {
+            AudioCommand::Play(play_args) => Some(&mut play_args.settings.start_delay),
+            command => command.tween_mut().map(|tween| &mut tween.start_delay),
+        }
+    }
+
+    /// Does this command start the given instance?
+    pub(crate) fn plays(&self, instance_handle: &InstanceHandle) -> bool {
+        match self {
+            AudioCommand::Play(play_args) => &play_args.instance_handle == instance_handle,
+            AudioCommand::PlayLayered(layers) => layers
+                .iter()
+                .any(|play_args| &play_args.instance_handle == instance_handle),
+            _ => false,
+        }
+    }
 }
 
 pub(crate) struct PlayAudioCommandArgs {
@@ -32,13 +115,6 @@ pub enum AudioCommandResult {
     Retry,
 }
 
-#[derive(PartialEq, Eq, Hash, Clone)]
-pub(crate) struct PlayAudioSettings {
-    pub source: Handle<AudioSource>,
-    pub intro_source: Option<Handle<AudioSource>>,
-    pub looped: bool,
-}
-
 /// Allows you to interact with a playing sound.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct InstanceHandle {
@@ -48,7 +124,7 @@ pub struct InstanceHandle {
 static NEXT_INSTANCE_HANDLE_ID: AtomicU64 = AtomicU64::new(0);
 
 impl InstanceHandle {
-    fn new() -> InstanceHandle {
+    pub(crate) fn new() -> InstanceHandle {
         let id = NEXT_INSTANCE_HANDLE_ID.fetch_add(1, Ordering::SeqCst);
         InstanceHandle { id }
     }
@@ -74,6 +150,15 @@ pub enum PlaybackState {
     },
     /// The instance is queued
     Queued,
+    /// The instance could not be played
+    ///
+    /// See [`SoundLimitPolicy`](crate::SoundLimitPolicy) and
+    /// [`AudioPlayFailed`](crate::AudioPlayFailed).
+    Failed,
+    /// The instance was not played because of a cooldown
+    ///
+    /// See [`AudioChannel::set_cooldown`].
+    Suppressed,
     /// The instance cannot be found anymore.
     /// This might might mean it was playing before and is stopped now,
     /// or it never played in the channel were you asked for it.
@@ -89,7 +174,10 @@ impl PlaybackState {
     /// Get the playback position in seconds
     pub fn position(&self) -> Option<f64> {
         match self {
-            PlaybackState::Queued | PlaybackState::Stopped => None,
+            PlaybackState::Queued
+            | PlaybackState::Failed
+            | PlaybackState::Suppressed
+            | PlaybackState::Stopped => None,
             PlaybackState::Playing { position }
             | PlaybackState::Paused { position }
             | PlaybackState::Pausing { position }
@@ -102,6 +190,9 @@ impl From<&InstanceState> for PlaybackState {
     fn from(state: &InstanceState) -> Self {
         let position = state.kira.position();
         match state.kira.state() {
+            kira::sound::static_sound::PlaybackState::Playing if state.fading_out => {
+                PlaybackState::Stopping { position }
+            }
             kira::sound::static_sound::PlaybackState::Playing => {
                 PlaybackState::Playing { position }
             }
@@ -141,16 +232,78 @@ pub trait AudioApp {
     /// struct Background;
     /// ```
     fn add_audio_channel<T: Resource>(&mut self) -> &mut Self;
+
+    /// Add a new audio channel with a chain of effects to the application
+    ///
+    /// All sounds in the channel are processed by the effects in the given order.
+    /// Effect parameters can be changed using [`AudioChannel::set_effect_parameter`].
+    ///
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use bevy_kira_audio::{AudioApp, AudioChannel, AudioEffect, AudioPlugin, FilterBuilder, ReverbBuilder};
+    ///
+    /// fn main() {
+    ///     App::new()
+    ///         .add_plugins(DefaultPlugins)
+    ///         .add_plugin(AudioPlugin)
+    ///         .add_audio_channel_with_effects::<World>(vec![
+    ///             AudioEffect::Filter(FilterBuilder::new().cutoff(20_000.)),
+    ///             AudioEffect::Reverb(ReverbBuilder::new().mix(0.2)),
+    ///         ])
+    ///         .add_startup_system(play)
+    ///         .run();
+    /// }
+    ///
+    /// fn play(world: Res<AudioChannel<World>>, asset_server: Res<AssetServer>) {
+    ///     world.play(asset_server.load("sounds/loop.ogg"));
+    /// }
+    ///
+    /// struct World;
+    /// ```
+    fn add_audio_channel_with_effects<T: Resource>(
+        &mut self,
+        effects: Vec<AudioEffect>,
+    ) -> &mut Self;
 }
 
 impl AudioApp for App {
     fn add_audio_channel<T: Resource>(&mut self) -> &mut Self {
-        self.add_system_to_stage(CoreStage::PostUpdate, play_audio_channel::<T>)
-            .add_system_to_stage(
-                CoreStage::PreUpdate,
-                update_instance_states::<T>.after(AudioSystemLabel::InstanceCleanup),
-            )
-            .insert_resource(AudioChannel::<T>::default())
+        self.add_audio_channel_with_effects::<T>(vec![])
+    }
+
+    fn add_audio_channel_with_effects<T: Resource>(
+        &mut self,
+        effects: Vec<AudioEffect>,
+    ) -> &mut Self {
+        // the track is created on startup, when the output was set up with the final settings
+        let mut effects = Some(effects);
+        self.add_startup_system_to_stage(
+            StartupStage::PreStartup,
+            move |audio_output: Option<NonSendMut<AudioOutput>>| {
+                if let (Some(mut audio_output), Some(effects)) = (audio_output, effects.take()) {
+                    audio_output.add_channel::<T>(effects);
+                }
+            },
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            play_audio_channel::<T>.label(AudioSystemLabel::PlayAudio),
+        )
+        .add_system_to_stage(
+            CoreStage::PreUpdate,
+            update_instance_states::<T>
+                .label(AudioSystemLabel::InstanceStates)
+                .after(AudioSystemLabel::InstanceCleanup),
+        )
+        .add_system_to_stage(
+            CoreStage::PreUpdate,
+            update_playlist::<T>.after(AudioSystemLabel::InstanceStates),
+        )
+        .add_system_to_stage(
+            CoreStage::PreUpdate,
+            update_music_sequencer::<T>.after(AudioSystemLabel::InstanceStates),
+        )
+        .insert_resource(AudioChannel::<T>::default())
     }
 }
 
@@ -161,6 +314,13 @@ impl AudioApp for App {
 pub struct AudioChannel<T> {
     pub(crate) commands: RwLock<VecDeque<AudioCommand>>,
     pub(crate) states: HashMap<InstanceHandle, PlaybackState>,
+    pub(crate) remaining_times: HashMap<InstanceHandle, Duration>,
+    pub(crate) instance_limit: Option<InstanceLimit>,
+    cooldown: Option<PlayCooldown>,
+    source_cooldowns: HashMap<HandleId, PlayCooldown>,
+    pub(crate) playlist: Option<Playlist>,
+    quantize_reference: Option<QuantizeReference>,
+    pub(crate) music_sequencer: Option<MusicSequencer>,
     _marker: PhantomData<T>,
 }
 
@@ -169,7 +329,14 @@ impl<T> Default for AudioChannel<T> {
         AudioChannel::<T> {
             commands: Default::default(),
             states: Default::default(),
-            _marker: PhantomData::default(),
+            remaining_times: Default::default(),
+            instance_limit: None,
+            cooldown: None,
+            source_cooldowns: Default::default(),
+            playlist: None,
+            quantize_reference: None,
+            music_sequencer: None,
+            _marker: PhantomData,
         }
     }
 }
@@ -177,33 +344,111 @@ impl<T> Default for AudioChannel<T> {
 impl<T> AudioChannel<T> {
     /// Play audio in the default channel
     ///
+    /// The returned command can be used to configure this single playback
+    /// and to get a handle to the playing instance.
+    ///
+    /// The play is only queued when the returned command is dropped. Commands issued while the
+    /// play command is still held, e.g. in a variable, run before the play. Get the handle in
+    /// the same statement as the play to control the instance right away.
+    ///
     /// ```edition2018
     /// # use bevy::prelude::*;
     /// # use bevy_kira_audio::Audio;
     ///
     /// fn my_system(asset_server: Res<AssetServer>, audio: Res<Audio>) {
     ///     audio.play(asset_server.load("audio.mp3"));
+    ///     let instance_handle = audio
+    ///         .play(asset_server.load("audio.mp3"))
+    ///         .with_volume(0.5)
+    ///         .handle();
+    ///     // the play was queued at the end of the statement above, so it runs before the pause
+    ///     audio.pause_instance(&instance_handle);
     /// }
     /// ```
-    pub fn play(&self, audio_source: Handle<AudioSource>) -> InstanceHandle {
-        let instance_handle = InstanceHandle::new();
+    pub fn play(&self, audio_source: Handle<AudioSource>) -> PlayAudioCommand<'_> {
+        PlayAudioCommand::new(audio_source, &self.commands)
+    }
+
+    /// Play audio while fading out all other sounds in the channel
+    ///
+    /// The volumes follow an equal-power curve over the given duration. The outgoing instances
+    /// report [`PlaybackState::Stopping`] and are stopped at the end of the crossfade.
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::Audio;
+    /// # use std::time::Duration;
+    ///
+    /// fn switch_music(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+    ///     audio
+    ///         .crossfade_to(asset_server.load("boss.ogg"), Duration::from_secs(3))
+    ///         .looped();
+    /// }
+    /// ```
+    pub fn crossfade_to(
+        &self,
+        audio_source: Handle<AudioSource>,
+        duration: Duration,
+    ) -> PlayAudioCommand<'_> {
+        PlayAudioCommand::new(audio_source, &self.commands).crossfade(duration)
+    }
+
+    /// Play audio on the next grid position of the channel's quantize reference
+    ///
+    /// The play waits until the reference instance reaches the next beat, bar, or custom grid
+    /// position of its tempo. It is scheduled on a clock of the audio output and starts within a
+    /// millisecond of the grid position. Plays aligned to a streamed reference start in the
+    /// frame closest to the grid position, which is accurate to about half a frame. The sound
+    /// plays right away if no
+    /// [reference](Self::set_quantize_reference) is set, the reference instance is not playing,
+    /// or the reference has no tempo. See [`QuantizeReference`] for an example.
+    pub fn play_quantized(
+        &self,
+        audio_source: Handle<AudioSource>,
+        quantize: Quantize,
+    ) -> PlayAudioCommand<'_> {
+        let command = PlayAudioCommand::new(audio_source, &self.commands);
+        match &self.quantize_reference {
+            Some(reference) => command.quantize(QuantizedStart::new(quantize, reference.clone())),
+            None => {
+                warn!("Quantized play without quantize reference starts right away");
+                command
+            }
+        }
+    }
 
+    /// Start all layers of the music on the same sample
+    ///
+    /// The layers start once all their audio sources are loaded. If one layer cannot play,
+    /// none of the layers play and all report [`PlaybackState::Failed`]. See [`LayeredMusic`]
+    /// for an example.
+    pub fn play_layered(&self, music: &LayeredMusic) -> LayeredInstance {
+        let instance = LayeredInstance::new(music);
         self.commands
             .write()
-            .push_front(AudioCommand::Play(PlayAudioCommandArgs {
-                settings: PlayAudioSettings {
-                    source: audio_source,
-                    intro_source: None,
-                    looped: false,
-                },
-                instance_handle: instance_handle.clone(),
-            }));
+            .push_front(AudioCommand::PlayLayered(music.play_args(&instance)));
+        instance
+    }
 
-        instance_handle
+    /// Fade the layers of the music in or out for the given intensity
+    ///
+    /// Layers are audible if the intensity is at least the
+    /// [intensity of the layer](crate::MusicLayer::intensity).
+    pub fn set_intensity(&self, music: &LayeredInstance, intensity: f64, tween: AudioTween) {
+        let mut commands = self.commands.write();
+        for (layer, volume) in music.volumes_at(intensity) {
+            commands.push_front(AudioCommand::SetInstanceVolume(
+                layer.clone(),
+                volume as f32,
+                tween,
+            ));
+        }
     }
 
     /// Play looped audio in the default channel
     ///
+    /// This is a shortcut for [`play(audio_source).looped()`](PlayAudioCommand::looped).
+    ///
     /// ```edition2018
     /// # use bevy::prelude::*;
     /// # use bevy_kira_audio::Audio;
@@ -213,24 +458,15 @@ impl<T> AudioChannel<T> {
     /// }
     /// ```
     pub fn play_looped(&self, audio_source: Handle<AudioSource>) -> InstanceHandle {
-        let instance_handle = InstanceHandle::new();
-
-        self.commands
-            .write()
-            .push_front(AudioCommand::Play(PlayAudioCommandArgs {
-                settings: PlayAudioSettings {
-                    source: audio_source,
-                    intro_source: None,
-                    looped: true,
-                },
-                instance_handle: instance_handle.clone(),
-            }));
-
-        instance_handle
+        self.play(audio_source).looped().handle()
     }
 
     /// Play looped audio in the default channel with an intro
     ///
+    /// The intro is played once and the looped audio starts directly after it. Both are
+    /// controlled through the same [`InstanceHandle`]. The playback position counts from the
+    /// start of the intro. Both sounds need to have the same sample rate.
+    ///
     /// ```edition2018
     /// # use bevy::prelude::*;
     /// # use bevy_kira_audio::Audio;
@@ -244,34 +480,27 @@ impl<T> AudioChannel<T> {
         intro_audio_source: Handle<AudioSource>,
         looped_audio_source: Handle<AudioSource>,
     ) -> InstanceHandle {
-        let instance_handle = InstanceHandle::new();
-
-        self.commands
-            .write()
-            .push_front(AudioCommand::Play(PlayAudioCommandArgs {
-                settings: PlayAudioSettings {
-                    source: looped_audio_source,
-                    intro_source: Some(intro_audio_source),
-                    looped: true,
-                },
-                instance_handle: instance_handle.clone(),
-            }));
-
-        instance_handle
+        self.play(looped_audio_source)
+            .looped()
+            .with_intro(intro_audio_source)
+            .handle()
     }
 
     /// Stop all audio in the default channel
     ///
+    /// The returned command can be given a tween to fade out the audio.
+    ///
     /// ```edition2018
     /// # use bevy::prelude::*;
-    /// # use bevy_kira_audio::Audio;
+    /// # use bevy_kira_audio::{Audio, AudioTween};
+    /// # use std::time::Duration;
     ///
     /// fn my_system(audio: Res<Audio>) {
-    ///     audio.stop();
+    ///     audio.stop().with_tween(AudioTween::linear(Duration::from_secs(2)));
     /// }
     /// ```
-    pub fn stop(&self) {
-        self.commands.write().push_front(AudioCommand::Stop);
+    pub fn stop(&self) -> TweenCommand<'_> {
+        TweenCommand::new(AudioCommand::Stop(AudioTween::default()), &self.commands)
     }
 
     /// Pause all audio in the default channel
@@ -284,8 +513,8 @@ impl<T> AudioChannel<T> {
     ///     audio.pause();
     /// }
     /// ```
-    pub fn pause(&self) {
-        self.commands.write().push_front(AudioCommand::Pause);
+    pub fn pause(&self) -> TweenCommand<'_> {
+        TweenCommand::new(AudioCommand::Pause(AudioTween::default()), &self.commands)
     }
 
     /// Resume all audio in the default channel
@@ -298,26 +527,32 @@ impl<T> AudioChannel<T> {
     ///     audio.resume();
     /// }
     /// ```
-    pub fn resume(&self) {
-        self.commands.write().push_front(AudioCommand::Resume);
+    pub fn resume(&self) -> TweenCommand<'_> {
+        TweenCommand::new(AudioCommand::Resume(AudioTween::default()), &self.commands)
     }
 
     /// Set the volume for the default channel
     ///
     /// The default value is 1.
     ///
+    /// The returned command can be given a tween to smoothly change the volume.
+    ///
     /// ```edition2018
     /// # use bevy::prelude::*;
-    /// # use bevy_kira_audio::Audio;
+    /// # use bevy_kira_audio::{Audio, AudioEasing, AudioTween};
+    /// # use std::time::Duration;
     ///
     /// fn my_system(audio: Res<Audio>) {
-    ///     audio.set_volume(0.5);
+    ///     audio
+    ///         .set_volume(0.5)
+    ///         .with_tween(AudioTween::new(Duration::from_secs(1), AudioEasing::OutPowi(2)));
     /// }
     /// ```
-    pub fn set_volume(&self, volume: f32) {
-        self.commands
-            .write()
-            .push_front(AudioCommand::SetVolume(volume));
+    pub fn set_volume(&self, volume: f32) -> TweenCommand<'_> {
+        TweenCommand::new(
+            AudioCommand::SetVolume(volume, AudioTween::default()),
+            &self.commands,
+        )
     }
 
     /// Set panning for the default channel
@@ -334,10 +569,11 @@ impl<T> AudioChannel<T> {
     ///     audio.set_panning(0.9);
     /// }
     /// ```
-    pub fn set_panning(&self, panning: f32) {
-        self.commands
-            .write()
-            .push_front(AudioCommand::SetPanning(panning));
+    pub fn set_panning(&self, panning: f32) -> TweenCommand<'_> {
+        TweenCommand::new(
+            AudioCommand::SetPanning(panning, AudioTween::default()),
+            &self.commands,
+        )
     }
 
     /// Set playback rate for the default channel
@@ -352,10 +588,337 @@ impl<T> AudioChannel<T> {
     ///     audio.set_playback_rate(2.0);
     /// }
     /// ```
-    pub fn set_playback_rate(&self, playback_rate: f32) {
+    pub fn set_playback_rate(&self, playback_rate: f32) -> TweenCommand<'_> {
+        TweenCommand::new(
+            AudioCommand::SetPlaybackRate(playback_rate, AudioTween::default()),
+            &self.commands,
+        )
+    }
+
+    /// Stop a single playback instance
+    ///
+    /// Other sounds in the channel keep playing.
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::Audio;
+    ///
+    /// fn my_system(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+    ///     let instance_handle = audio.play(asset_server.load("audio.mp3")).handle();
+    ///     audio.stop_instance(&instance_handle);
+    /// }
+    /// ```
+    pub fn stop_instance(&self, instance_handle: &InstanceHandle) -> TweenCommand<'_> {
+        TweenCommand::new(
+            AudioCommand::StopInstance(instance_handle.clone(), AudioTween::default()),
+            &self.commands,
+        )
+    }
+
+    /// Pause a single playback instance
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::Audio;
+    ///
+    /// fn my_system(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+    ///     let instance_handle = audio.play(asset_server.load("audio.mp3")).handle();
+    ///     audio.pause_instance(&instance_handle);
+    /// }
+    /// ```
+    pub fn pause_instance(&self, instance_handle: &InstanceHandle) -> TweenCommand<'_> {
+        TweenCommand::new(
+            AudioCommand::PauseInstance(instance_handle.clone(), AudioTween::default()),
+            &self.commands,
+        )
+    }
+
+    /// Resume a single paused playback instance
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::{Audio, InstanceHandle};
+    ///
+    /// fn my_system(audio: Res<Audio>, instance_handle: Res<InstanceHandle>) {
+    ///     audio.resume_instance(&instance_handle);
+    /// }
+    /// ```
+    pub fn resume_instance(&self, instance_handle: &InstanceHandle) -> TweenCommand<'_> {
+        TweenCommand::new(
+            AudioCommand::ResumeInstance(instance_handle.clone(), AudioTween::default()),
+            &self.commands,
+        )
+    }
+
+    /// Set the volume of a single playback instance
+    ///
+    /// The volume is relative to the volume of the channel.
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::{Audio, InstanceHandle};
+    ///
+    /// fn my_system(audio: Res<Audio>, instance_handle: Res<InstanceHandle>) {
+    ///     audio.set_instance_volume(&instance_handle, 0.5);
+    /// }
+    /// ```
+    pub fn set_instance_volume(
+        &self,
+        instance_handle: &InstanceHandle,
+        volume: f32,
+    ) -> TweenCommand<'_> {
+        TweenCommand::new(
+            AudioCommand::SetInstanceVolume(instance_handle.clone(), volume, AudioTween::default()),
+            &self.commands,
+        )
+    }
+
+    /// Set the panning of a single playback instance
+    ///
+    /// See [`set_panning`](Self::set_panning) for possible values. The panning of the channel
+    /// is applied on top of the panning of the instance.
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::{Audio, InstanceHandle};
+    ///
+    /// fn my_system(audio: Res<Audio>, instance_handle: Res<InstanceHandle>) {
+    ///     audio.set_instance_panning(&instance_handle, 0.9);
+    /// }
+    /// ```
+    pub fn set_instance_panning(
+        &self,
+        instance_handle: &InstanceHandle,
+        panning: f32,
+    ) -> TweenCommand<'_> {
+        TweenCommand::new(
+            AudioCommand::SetInstancePanning(
+                instance_handle.clone(),
+                panning,
+                AudioTween::default(),
+            ),
+            &self.commands,
+        )
+    }
+
+    /// Set the playback rate of a single playback instance
+    ///
+    /// The playback rate is relative to the playback rate of the channel.
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::{Audio, InstanceHandle};
+    ///
+    /// fn my_system(audio: Res<Audio>, instance_handle: Res<InstanceHandle>) {
+    ///     audio.set_instance_playback_rate(&instance_handle, 2.0);
+    /// }
+    /// ```
+    pub fn set_instance_playback_rate(
+        &self,
+        instance_handle: &InstanceHandle,
+        playback_rate: f32,
+    ) -> TweenCommand<'_> {
+        TweenCommand::new(
+            AudioCommand::SetInstancePlaybackRate(
+                instance_handle.clone(),
+                playback_rate,
+                AudioTween::default(),
+            ),
+            &self.commands,
+        )
+    }
+
+    /// Move the playback position of a single instance
+    ///
+    /// The position is given in seconds from the start of the sound.
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::{Audio, InstanceHandle};
+    ///
+    /// fn my_system(audio: Res<Audio>, instance_handle: Res<InstanceHandle>) {
+    ///     audio.seek_to(&instance_handle, 4.2);
+    /// }
+    /// ```
+    pub fn seek_to(&self, instance_handle: &InstanceHandle, position: f64) {
+        self.commands
+            .write()
+            .push_front(AudioCommand::SeekTo(instance_handle.clone(), position));
+    }
+
+    /// Move the playback position of a single instance by the given amount of seconds
+    ///
+    /// Negative values move the position back.
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::{Audio, InstanceHandle};
+    ///
+    /// fn skip_ahead(audio: Res<Audio>, instance_handle: Res<InstanceHandle>) {
+    ///     audio.seek_by(&instance_handle, 10.);
+    /// }
+    /// ```
+    pub fn seek_by(&self, instance_handle: &InstanceHandle, delta: f64) {
+        self.commands
+            .write()
+            .push_front(AudioCommand::SeekBy(instance_handle.clone(), delta));
+    }
+
+    /// Stop looping a single instance and let it play out to the end of its sound
+    ///
+    /// Kira cannot change the looping of a playing sound, so the instance is replaced by a new,
+    /// non-looping sound at the same position. The switch can skip or repeat a few milliseconds.
+    /// Instances that are still playing their intro switch after the intro.
+    /// Streamed sounds cannot stop looping.
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::{Audio, InstanceHandle};
+    ///
+    /// fn end_music(audio: Res<Audio>, music: Res<InstanceHandle>) {
+    ///     audio.disable_looping(&music);
+    /// }
+    /// ```
+    pub fn disable_looping(&self, instance_handle: &InstanceHandle) {
         self.commands
             .write()
-            .push_front(AudioCommand::SetPlaybackRate(playback_rate));
+            .push_front(AudioCommand::DisableLooping(instance_handle.clone()));
+    }
+
+    /// Change a parameter of an effect on this channel
+    ///
+    /// The index refers to the position of the effect in the list given to
+    /// [`add_audio_channel_with_effects`](AudioApp::add_audio_channel_with_effects).
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::{Audio, AudioTween, EffectParameter};
+    /// # use std::time::Duration;
+    ///
+    /// fn muffle(audio: Res<Audio>) {
+    ///     audio
+    ///         .set_effect_parameter(0, EffectParameter::FilterCutoff(500.))
+    ///         .with_tween(AudioTween::linear(Duration::from_millis(500)));
+    /// }
+    /// ```
+    pub fn set_effect_parameter(
+        &self,
+        effect: usize,
+        parameter: EffectParameter,
+    ) -> TweenCommand<'_> {
+        TweenCommand::new(
+            AudioCommand::SetEffectParameter(effect, parameter, AudioTween::default()),
+            &self.commands,
+        )
+    }
+
+    /// Limit the number of instances playing in this channel at a time
+    ///
+    /// By default, the number of instances is only limited by
+    /// [`AudioSettings::sound_capacity`](crate::AudioSettings::sound_capacity) for all channels.
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::{Audio, InstanceLimit, SoundLimitPolicy};
+    ///
+    /// fn limit_sound_effects(mut audio: ResMut<Audio>) {
+    ///     audio.set_instance_limit(Some(InstanceLimit {
+    ///         max_instances: 16,
+    ///         policy: SoundLimitPolicy::StealOldest,
+    ///     }));
+    /// }
+    /// ```
+    pub fn set_instance_limit(&mut self, instance_limit: Option<InstanceLimit>) {
+        self.instance_limit = instance_limit;
+    }
+
+    /// Limit how often the same sound can be played in this channel
+    ///
+    /// Plays exceeding the cooldown are dropped and report [`PlaybackState::Suppressed`].
+    /// Cooldowns of single sources set with [`set_source_cooldown`](Self::set_source_cooldown)
+    /// take precedence.
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::{Audio, PlayCooldown};
+    /// # use std::time::Duration;
+    ///
+    /// fn limit_repeated_sounds(mut audio: ResMut<Audio>) {
+    ///     audio.set_cooldown(Some(PlayCooldown {
+    ///         max_plays: 2,
+    ///         window: Duration::from_millis(100),
+    ///     }));
+    /// }
+    /// ```
+    pub fn set_cooldown(&mut self, cooldown: Option<PlayCooldown>) {
+        self.cooldown = cooldown;
+    }
+
+    /// Limit how often the given sound can be played in this channel
+    ///
+    /// Passing `None` removes the cooldown of the source. The channel's cooldown still applies.
+    pub fn set_source_cooldown(
+        &mut self,
+        source: &Handle<AudioSource>,
+        cooldown: Option<PlayCooldown>,
+    ) {
+        match cooldown {
+            Some(cooldown) => self.source_cooldowns.insert(source.id, cooldown),
+            None => self.source_cooldowns.remove(&source.id),
+        };
+    }
+
+    pub(crate) fn cooldown(&self, source: &Handle<AudioSource>) -> Option<PlayCooldown> {
+        self.source_cooldowns
+            .get(&source.id)
+            .copied()
+            .or(self.cooldown)
+    }
+
+    /// Play the tracks of the given playlist one after another
+    ///
+    /// The first track starts in the next frame. Replacing or removing a playlist does not stop
+    /// its current track. See [`Playlist`] for an example.
+    pub fn set_playlist(&mut self, playlist: Option<Playlist>) {
+        self.playlist = playlist;
+    }
+
+    /// Set the instance whose beats [quantized plays](Self::play_quantized) are aligned to
+    pub fn set_quantize_reference(&mut self, reference: Option<QuantizeReference>) {
+        self.quantize_reference = reference;
+    }
+
+    /// The instance whose beats quantized plays are aligned to
+    pub fn quantize_reference(&self) -> Option<&QuantizeReference> {
+        self.quantize_reference.as_ref()
+    }
+
+    /// Play the segments of a music sequence in this channel
+    ///
+    /// Replacing or removing a sequencer does not stop its current segment.
+    /// See [`MusicSequencer`] for an example.
+    pub fn set_music_sequencer(&mut self, sequencer: Option<MusicSequencer>) {
+        self.music_sequencer = sequencer;
+    }
+
+    /// The music sequencer of this channel
+    pub fn music_sequencer(&self) -> Option<&MusicSequencer> {
+        self.music_sequencer.as_ref()
+    }
+
+    /// The music sequencer of this channel, e.g. to request another segment
+    pub fn music_sequencer_mut(&mut self) -> Option<&mut MusicSequencer> {
+        self.music_sequencer.as_mut()
+    }
+
+    /// The playlist of this channel
+    pub fn playlist(&self) -> Option<&Playlist> {
+        self.playlist.as_ref()
+    }
+
+    /// The playlist of this channel, e.g. to add tracks or change its mode
+    pub fn playlist_mut(&mut self) -> Option<&mut Playlist> {
+        self.playlist.as_mut()
     }
 
     /// Get state for a playback instance.
@@ -367,17 +930,19 @@ impl<T> AudioChannel<T> {
                 self.commands
                     .read()
                     .iter()
-                    .find(|command| match command {
-                        AudioCommand::Play(PlayAudioCommandArgs {
-                            instance_handle: handle,
-                            settings: _,
-                        }) => handle.id == instance_handle.id,
-                        _ => false,
-                    })
+                    .find(|command| command.plays(&instance_handle))
                     .map(|_| PlaybackState::Queued)
                     .unwrap_or(PlaybackState::Stopped)
             })
     }
+
+    /// Time until a playing instance ends or loops, if its length is known
+    ///
+    /// Includes the playback rate of the instance and its channel. Reversed instances end
+    /// at the start of the sound.
+    pub(crate) fn remaining(&self, instance_handle: &InstanceHandle) -> Option<Duration> {
+        self.remaining_times.get(instance_handle).copied()
+    }
 }
 
 #[cfg(test)]
@@ -391,8 +956,21 @@ mod test {
         let audio = AudioChannel::<Audio>::default();
         let audio_handle: Handle<AudioSource> =
             Handle::<AudioSource>::weak(HandleId::default::<AudioSource>());
-        let instance_handle = audio.play(audio_handle);
+        let instance_handle = audio.play(audio_handle).handle();
+
+        assert_eq!(audio.state(instance_handle), PlaybackState::Queued);
+    }
+
+    #[test]
+    fn play_command_is_queued_when_dropped() {
+        let audio = AudioChannel::<Audio>::default();
+        let audio_handle: Handle<AudioSource> =
+            Handle::<AudioSource>::weak(HandleId::default::<AudioSource>());
+        let play_command = audio.play(audio_handle).with_volume(0.5).looped();
+        let instance_handle = play_command.handle();
+        assert_eq!(audio.state(instance_handle.clone()), PlaybackState::Stopped);
 
+        drop(play_command);
         assert_eq!(audio.state(instance_handle), PlaybackState::Queued);
     }
 
@@ -404,6 +982,38 @@ mod test {
         assert_eq!(audio.state(instance_handle), PlaybackState::Stopped);
     }
 
+    #[test]
+    fn instance_commands_target_their_instance() {
+        let audio = AudioChannel::<Audio>::default();
+        let instance_handle = InstanceHandle::new();
+        audio.stop_instance(&instance_handle);
+        audio.set_instance_volume(&instance_handle, 0.5);
+
+        let commands = audio.commands.read();
+        assert_eq!(commands.len(), 2);
+        assert!(commands
+            .iter()
+            .all(|command| command.target_instance() == Some(&instance_handle)));
+    }
+
+    #[test]
+    fn commands_carry_configured_tween() {
+        let audio = AudioChannel::<Audio>::default();
+        let tween = AudioTween::linear(Duration::from_secs(2)).with_delay(Duration::from_secs(1));
+        audio.stop().with_tween(tween);
+        audio.pause();
+
+        let mut commands = audio.commands.write();
+        assert_eq!(
+            commands.pop_back().unwrap().tween_mut().copied(),
+            Some(tween)
+        );
+        assert_eq!(
+            commands.pop_back().unwrap().tween_mut().copied(),
+            Some(AudioTween::default())
+        );
+    }
+
     #[test]
     fn state_is_fetched_from_state_map() {
         let mut audio = AudioChannel::<Audio>::default();
@@ -1,53 +1,495 @@
-use crate::audio::{AudioCommand, AudioCommandResult, InstanceHandle, PlayAudioSettings};
+use crate::audio::{
+    AudioCommand, AudioCommandResult, InstanceHandle, PlayAudioCommandArgs, PlaybackState,
+};
+use crate::clock::AudioClock;
+use crate::effects::{AudioEffect, EffectHandle, EffectParameter, SetEffectParameterError};
+use crate::events::{
+    AudioBar, AudioBeat, AudioFinished, AudioLooped, AudioPlayFailed, AudioStarted, InstanceEvent,
+    InstanceEventWriters,
+};
+use crate::play::PlayAudioSettings;
+use crate::quantize::QuantizedStart;
+use crate::recording::AudioRecording;
 use bevy::prelude::*;
 use std::any::TypeId;
 
-use crate::settings::AudioSettings;
-use crate::source::AudioSource;
+use crate::manager::Manager;
+use crate::settings::{AudioSettings, InstanceLimit, PlayCooldown, SoundLimitPolicy};
+use crate::source::group::GroupPlayback;
+#[cfg(all(
+    any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+    not(target_arch = "wasm32")
+))]
+use crate::source::streaming_settings;
+use crate::source::{AudioSource, AudioSourceData};
+use crate::spatial::Attenuation;
+use crate::tempo::Tempo;
+use crate::tween::AudioTween;
 use crate::AudioChannel;
+use bevy::asset::HandleId;
 use bevy::ecs::system::Resource;
-use kira::manager::AudioManager;
-use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle};
+use kira::clock::{ClockHandle, ClockTime};
+use kira::dsp::Frame;
+use kira::manager::error::PlaySoundError;
+use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings};
+#[cfg(all(
+    any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+    not(target_arch = "wasm32")
+))]
+use kira::sound::{
+    streaming::{StreamingSoundData, StreamingSoundHandle},
+    FromFileError,
+};
+use kira::track::effect::panning_control::{PanningControlBuilder, PanningControlHandle};
+use kira::track::{TrackBuilder, TrackHandle};
 use kira::tween::Tween;
-use kira::{CommandError, LoopBehavior};
-use std::collections::HashMap;
+use kira::{ClockSpeed, CommandError, PlaybackRate, StartTime, Volume};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+#[cfg(all(
+    any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+    not(target_arch = "wasm32")
+))]
+use std::io::Cursor;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Non-send resource that acts as audio output
 ///
 /// This struct holds the [kira::manager::AudioManager] to play audio through. It also
 /// keeps track of all audio instance handles and which sounds are playing in which channel.
 pub struct AudioOutput {
-    manager: Option<AudioManager>,
+    manager: Option<Manager>,
     instances: HashMap<TypeId, Vec<InstanceState>>,
     channels: HashMap<TypeId, ChannelState>,
+    intro_sounds: HashMap<(HandleId, HandleId), IntroSound>,
+    /// Sounds cut at the end of their loop region by source and end frame
+    loop_regions: HashMap<(HandleId, usize), LoopRegion>,
+    /// Picked variants of sound groups
+    group_playbacks: HashMap<HandleId, GroupPlayback>,
+    events: Vec<InstanceEvent>,
+    /// The last instances per channel that failed to play or were suppressed
+    discarded: HashMap<TypeId, VecDeque<(InstanceHandle, PlaybackState)>>,
+    sound_limit_policy: SoundLimitPolicy,
+    /// Clocks that start synchronized plays, dropped once they ticked
+    start_clocks: Vec<ClockHandle>,
+    /// Clock that quantized plays are scheduled on, added with the first quantized play
+    quantize_clock: Option<ClockHandle>,
+    /// Number of started instances, used to order instances by age
+    started_instances: u64,
+}
+
+/// Speed of the clocks that start synchronized plays
+///
+/// Start clocks are removed after their first tick following the start.
+const START_CLOCK_TICKS_PER_SECOND: f64 = 1000.;
+
+/// Speed of the clock that quantized plays are scheduled on
+const QUANTIZE_CLOCK_TICKS_PER_SECOND: f64 = 1000.;
+
+/// Maximum number of discarded instances per channel that report their state
+const MAX_DISCARDED_INSTANCES: usize = 64;
+
+/// Frames of a sound up to the end of its loop region
+///
+/// The source frames are kept to detect changed or unloaded assets.
+struct LoopRegion {
+    source: Arc<Vec<Frame>>,
+    frames: Arc<Vec<Frame>>,
+}
+
+impl LoopRegion {
+    fn new(sound: &StaticSoundData, end_frame: usize) -> Self {
+        LoopRegion {
+            source: sound.frames.clone(),
+            frames: Arc::new(sound.frames[..end_frame].to_vec()),
+        }
+    }
+
+    fn is_built_from(&self, sound: &StaticSoundData) -> bool {
+        Arc::ptr_eq(&self.source, &sound.frames)
+    }
+
+    fn source_unloaded(&self) -> bool {
+        Arc::strong_count(&self.source) == 1
+    }
+}
+
+/// The full sound of a looping instance, to let it play out when looping is disabled
+struct PlayOut {
+    sound: StaticSoundData,
+    /// Duration of the intro played before the looped sound
+    intro_duration: f64,
+}
+
+/// Frames of an intro followed by a looped sound
+///
+/// The source frames are kept to detect changed or unloaded assets.
+struct IntroSound {
+    intro: Arc<Vec<Frame>>,
+    looped: Arc<Vec<Frame>>,
+    frames: Arc<Vec<Frame>>,
+}
+
+impl IntroSound {
+    fn new(intro: &StaticSoundData, looped: &StaticSoundData) -> Self {
+        let mut frames = Vec::with_capacity(intro.frames.len() + looped.frames.len());
+        frames.extend_from_slice(&intro.frames);
+        frames.extend_from_slice(&looped.frames);
+        IntroSound {
+            intro: intro.frames.clone(),
+            looped: looped.frames.clone(),
+            frames: Arc::new(frames),
+        }
+    }
+
+    fn is_built_from(&self, intro: &StaticSoundData, looped: &StaticSoundData) -> bool {
+        Arc::ptr_eq(&self.intro, &intro.frames) && Arc::ptr_eq(&self.looped, &looped.frames)
+    }
+
+    fn sources_unloaded(&self) -> bool {
+        Arc::strong_count(&self.intro) == 1 || Arc::strong_count(&self.looped) == 1
+    }
 }
 
 pub(crate) struct InstanceState {
-    pub(crate) kira: StaticSoundHandle,
+    pub(crate) kira: SoundHandle,
     pub(crate) handle: InstanceHandle,
+    /// Volume of the instance without spatial attenuation
+    pub(crate) volume: f64,
+    /// Volume multiplier from the distance to the audio receiver
+    pub(crate) attenuation: f64,
+    /// Attenuation configured for the played audio source
+    pub(crate) source_attenuation: Option<Attenuation>,
+    /// Playback rate of the instance relative to the channel playback rate
+    pub(crate) playback_rate: f64,
+    /// Last panning set for positional audio
+    pub(crate) panning: Option<f64>,
+    /// Volume multiplier of a running crossfade
+    fade: f64,
+    /// Whether the instance is faded out by a crossfade and stopped at its end
+    pub(crate) fading_out: bool,
+    /// Whether the instance was stopped, before Kira reports it as stopping
+    stopped: bool,
+    /// Whether the sound waits for its start time on a clock
+    scheduled: bool,
+    /// Whether the stop of the instance waits for a clock
+    stop_scheduled: bool,
+    /// Whether the sound has a loop behavior
+    ///
+    /// Only looping sounds are checked for jumps back to their loop start, for which
+    /// `cleanup_stopped_instances` sends an [`AudioLooped`] event.
+    loops: bool,
+    play_out: Option<PlayOut>,
+    /// Whether the sound plays in reverse, so it loops by jumping forward
+    reverse: bool,
+    /// Position at the last cleanup, reset when seeking
+    last_position: Option<f64>,
+    /// Instances with a lower start order were started earlier
+    start_order: u64,
+    /// Tempo of the played audio source to send beat events
+    tempo: Option<Tempo>,
+    /// Position at which the sound ends or loops back, unknown for streamed sounds
+    end: Option<f64>,
+    priority: i32,
+}
+
+impl InstanceState {
+    /// Whether the instance is playing or paused and not about to stop
+    fn is_active(&self) -> bool {
+        !self.stopped
+            && !matches!(
+                self.kira.state(),
+                kira::sound::static_sound::PlaybackState::Stopping
+                    | kira::sound::static_sound::PlaybackState::Stopped
+            )
+    }
+
+    /// Time until the instance ends or loops at the given playback rate of its channel
+    fn remaining(&self, channel_playback_rate: f64) -> Option<Duration> {
+        let end = self.end?;
+        let playback_rate = (self.playback_rate * channel_playback_rate).abs();
+        if playback_rate <= 0. {
+            return None;
+        }
+        let position = self.kira.position();
+        let left = if self.reverse {
+            position
+        } else {
+            end - position
+        };
+        Some(Duration::from_secs_f64((left / playback_rate).max(0.)))
+    }
+
+    /// Whether the instance waits for a clock to start or stop
+    ///
+    /// Scheduled instances do not free their place in Kira until the clock reaches their time.
+    fn is_scheduled(&self) -> bool {
+        self.scheduled || self.stop_scheduled
+    }
+
+    /// Stop the instance and count it as inactive right away
+    ///
+    /// Kira only updates the state of the instance when it processes the command.
+    fn stop(&mut self, tween: Tween) -> Result<(), CommandError> {
+        let stop_scheduled = matches!(tween.start_time, StartTime::ClockTime(_));
+        self.kira.stop(tween)?;
+        self.stopped = true;
+        self.stop_scheduled = stop_scheduled;
+        Ok(())
+    }
+
+    /// Volume of the Kira sound including attenuation and crossfades
+    fn kira_volume(&self) -> f64 {
+        self.volume * self.attenuation * self.fade
+    }
+
+    fn set_fade(&mut self, fade: f64) {
+        self.fade = fade;
+        if let Err(error) = self.kira.set_volume(self.kira_volume(), Tween::default()) {
+            error!("Failed to crossfade instance: {:?}", error);
+        }
+    }
+
+    /// Check if a sound waiting for a clock started to move since the last check
+    fn update_scheduled(&mut self) {
+        if self.scheduled
+            && self
+                .last_position
+                .map_or(false, |last_position| last_position != self.kira.position())
+        {
+            self.scheduled = false;
+        }
+    }
+
+    /// Check if the playback position jumped back to the loop start since the last check
+    fn has_looped(&mut self) -> bool {
+        let position = self.kira.position();
+        let last_position = self.last_position.replace(position);
+        if !self.loops || self.kira.state() != kira::sound::static_sound::PlaybackState::Playing {
+            return false;
+        }
+        match last_position {
+            Some(last_position) if self.reverse => position > last_position,
+            Some(last_position) => position < last_position,
+            None => false,
+        }
+    }
+}
+
+/// Handle of a playing Kira sound
+pub(crate) enum SoundHandle {
+    Static(StaticSoundHandle),
+    #[cfg(all(
+        any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+        not(target_arch = "wasm32")
+    ))]
+    Streaming(StreamingSoundHandle<FromFileError>),
+}
+
+macro_rules! delegate {
+    ($self:ident, $handle:ident => $call:expr) => {
+        match $self {
+            SoundHandle::Static($handle) => $call,
+            #[cfg(all(
+                any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+                not(target_arch = "wasm32")
+            ))]
+            SoundHandle::Streaming($handle) => $call,
+        }
+    };
+}
+
+impl SoundHandle {
+    pub(crate) fn state(&self) -> kira::sound::static_sound::PlaybackState {
+        delegate!(self, handle => handle.state())
+    }
+
+    pub(crate) fn position(&self) -> f64 {
+        delegate!(self, handle => handle.position())
+    }
+
+    pub(crate) fn set_volume(&mut self, volume: f64, tween: Tween) -> Result<(), CommandError> {
+        delegate!(self, handle => handle.set_volume(volume, tween))
+    }
+
+    pub(crate) fn set_panning(&mut self, panning: f64, tween: Tween) -> Result<(), CommandError> {
+        delegate!(self, handle => handle.set_panning(panning, tween))
+    }
+
+    pub(crate) fn set_playback_rate(
+        &mut self,
+        playback_rate: f64,
+        tween: Tween,
+    ) -> Result<(), CommandError> {
+        delegate!(self, handle => handle.set_playback_rate(playback_rate, tween))
+    }
+
+    pub(crate) fn pause(&mut self, tween: Tween) -> Result<(), CommandError> {
+        delegate!(self, handle => handle.pause(tween))
+    }
+
+    pub(crate) fn resume(&mut self, tween: Tween) -> Result<(), CommandError> {
+        delegate!(self, handle => handle.resume(tween))
+    }
+
+    pub(crate) fn stop(&mut self, tween: Tween) -> Result<(), CommandError> {
+        delegate!(self, handle => handle.stop(tween))
+    }
+
+    pub(crate) fn seek_to(&mut self, position: f64) -> Result<(), CommandError> {
+        delegate!(self, handle => handle.seek_to(position))
+    }
+
+    pub(crate) fn seek_by(&mut self, amount: f64) -> Result<(), CommandError> {
+        delegate!(self, handle => handle.seek_by(amount))
+    }
+
+    /// Log errors that happened while decoding a streaming sound
+    fn log_errors(&mut self) {
+        #[cfg(all(
+            any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+            not(target_arch = "wasm32")
+        ))]
+        if let SoundHandle::Streaming(handle) = self {
+            while let Some(error) = handle.pop_error() {
+                error!("Failed to stream sound: {}", error);
+            }
+        }
+    }
 }
 
 impl FromWorld for AudioOutput {
     fn from_world(world: &mut World) -> Self {
         let settings = world.remove_resource::<AudioSettings>().unwrap_or_default();
-        let manager = AudioManager::new(settings.into());
-        if let Err(ref setup_error) = manager {
-            warn!("Failed to setup audio: {:?}", setup_error);
-        }
-
         Self {
-            manager: manager.ok(),
+            manager: Manager::new(&settings),
+            discarded: HashMap::default(),
+            sound_limit_policy: settings.sound_limit_policy,
+            start_clocks: vec![],
+            quantize_clock: None,
+            started_instances: 0,
             instances: HashMap::default(),
             channels: HashMap::default(),
+            intro_sounds: HashMap::default(),
+            loop_regions: HashMap::default(),
+            group_playbacks: HashMap::default(),
+            events: vec![],
         }
     }
 }
 
 impl AudioOutput {
-    fn stop(&mut self, channel: &TypeId) -> AudioCommandResult {
+    /// Process the given duration of audio
+    ///
+    /// This only has an effect with the [`AudioBackend::Mock`](crate::AudioBackend::Mock)
+    /// backend. Other backends process audio continuously on their own.
+    ///
+    /// ```edition2018
+    /// # use bevy::prelude::*;
+    /// # use bevy_kira_audio::AudioOutput;
+    /// fn tick_audio(mut audio_output: NonSendMut<AudioOutput>, time: Res<Time>) {
+    ///     audio_output.tick(time.delta());
+    /// }
+    /// ```
+    pub fn tick(&mut self, duration: Duration) {
+        if let Some(manager) = self.manager.as_mut() {
+            manager.tick(duration, |_| {});
+        }
+    }
+
+    /// Process the given duration of audio and return the main output
+    ///
+    /// Returns `None` if the [`AudioBackend::Mock`](crate::AudioBackend::Mock) backend is
+    /// not used. The output is rendered with the sample rate of the mock backend.
+    pub fn render(&mut self, duration: Duration) -> Option<AudioRecording> {
+        let manager = self.manager.as_mut()?;
+        let mut recording = AudioRecording::new(manager.mock_sample_rate()?);
+        manager.tick(duration, |frame| recording.frames.push(frame));
+
+        Some(recording)
+    }
+
+    /// Create a stopped clock ticking the given number of times per minute
+    ///
+    /// Returns `None` if the clock could not be created, for example because
+    /// [`AudioSettings::clock_capacity`](crate::AudioSettings::clock_capacity) clocks exist.
+    pub fn add_clock(&mut self, ticks_per_minute: f64) -> Option<AudioClock> {
+        let manager = self.manager.as_mut()?;
+        match manager.add_clock(ClockSpeed::TicksPerMinute(ticks_per_minute)) {
+            Ok(handle) => Some(AudioClock::new(handle)),
+            Err(error) => {
+                error!("Failed to create clock: {:?}", error);
+                None
+            }
+        }
+    }
+
+    /// Create the mixer sub-track for a new channel
+    pub(crate) fn add_channel<T: Resource>(&mut self, effects: Vec<AudioEffect>) {
+        let channel_state = self.channels.entry(TypeId::of::<T>()).or_default();
+        if channel_state.track.is_some() {
+            return;
+        }
+        if let Some(manager) = self.manager.as_mut() {
+            let mut track_builder = TrackBuilder::new().volume(channel_state.volume);
+            let effects = effects
+                .into_iter()
+                .map(|effect| effect.add_to(&mut track_builder))
+                .collect();
+            let panning = track_builder.add_effect(PanningControlBuilder(channel_state.panning));
+            match manager.add_sub_track(track_builder) {
+                Ok(track) => {
+                    channel_state.track = Some(ChannelTrack {
+                        track,
+                        panning,
+                        effects,
+                    })
+                }
+                Err(error) => error!("Failed to create track for audio channel: {:?}", error),
+            }
+        }
+    }
+
+    /// Update the spatial attenuation and panning of an instance in any channel
+    ///
+    /// The attenuation is calculated from the attenuation configured for the audio source
+    /// of the instance.
+    pub(crate) fn set_spatial(
+        &mut self,
+        instance_handle: &InstanceHandle,
+        panning: f64,
+        attenuation: impl FnOnce(Option<&Attenuation>) -> f64,
+    ) {
+        let instance = self
+            .instances
+            .values_mut()
+            .flat_map(|instances| instances.iter_mut())
+            .find(|instance| &instance.handle == instance_handle);
+        if let Some(instance) = instance {
+            let attenuation = attenuation(instance.source_attenuation.as_ref());
+            if instance.attenuation != attenuation {
+                instance.attenuation = attenuation;
+                if let Err(error) = instance
+                    .kira
+                    .set_volume(instance.kira_volume(), Tween::default())
+                {
+                    error!("Failed to set spatial volume for instance: {:?}", error);
+                }
+            }
+            if instance.panning != Some(panning) {
+                instance.panning = Some(panning);
+                if let Err(error) = instance.kira.set_panning(panning, Tween::default()) {
+                    error!("Failed to set spatial panning for instance: {:?}", error);
+                }
+            }
+        }
+    }
+
+    fn stop(&mut self, channel: &TypeId, tween: &AudioTween) -> AudioCommandResult {
         if let Some(instances) = self.instances.get_mut(channel) {
             for instance in instances {
-                match instance.kira.stop(Tween::default()) {
+                match instance.stop(tween.into()) {
                     Err(CommandError::CommandQueueFull) => {
                         return AudioCommandResult::Retry;
                     }
@@ -62,144 +504,954 @@ impl AudioOutput {
         AudioCommandResult::Ok
     }
 
-    fn pause(&mut self, channel: &TypeId) {
+    fn pause(&mut self, channel: &TypeId, tween: &AudioTween) -> AudioCommandResult {
         if let Some(instances) = self.instances.get_mut(channel) {
             for instance in instances.iter_mut() {
                 if kira::sound::static_sound::PlaybackState::Playing == instance.kira.state() {
-                    if let Err(error) = instance.kira.pause(Tween::default()) {
-                        error!("Failed to pause instance: {:?}", error);
+                    if let AudioCommandResult::Retry =
+                        command_result(instance.kira.pause(tween.into()), "pause instance")
+                    {
+                        return AudioCommandResult::Retry;
                     }
                 }
             }
         }
+
+        AudioCommandResult::Ok
     }
 
-    fn resume(&mut self, channel: &TypeId) {
+    fn resume(&mut self, channel: &TypeId, tween: &AudioTween) -> AudioCommandResult {
         if let Some(instances) = self.instances.get_mut(channel) {
             for instance in instances.iter_mut() {
                 if let kira::sound::static_sound::PlaybackState::Paused = instance.kira.state() {
-                    if let Err(error) = instance.kira.resume(Tween::default()) {
-                        error!("Failed to resume instance: {:?}", error);
+                    if let AudioCommandResult::Retry =
+                        command_result(instance.kira.resume(tween.into()), "resume instance")
+                    {
+                        return AudioCommandResult::Retry;
                     }
                 }
             }
         }
+
+        AudioCommandResult::Ok
     }
 
-    fn set_volume(&mut self, channel: &TypeId, volume: f64) {
+    fn set_volume(
+        &mut self,
+        channel: &TypeId,
+        volume: f64,
+        tween: &AudioTween,
+    ) -> AudioCommandResult {
+        let channel_state = self.channels.entry(*channel).or_default();
+        channel_state.volume = volume;
+        if let Some(track) = channel_state.track.as_ref() {
+            return command_result(
+                track.track.set_volume(volume, tween.into()),
+                "set volume for channel",
+            );
+        }
+
+        AudioCommandResult::Ok
+    }
+
+    fn set_panning(
+        &mut self,
+        channel: &TypeId,
+        panning: f64,
+        tween: &AudioTween,
+    ) -> AudioCommandResult {
+        let channel_state = self.channels.entry(*channel).or_default();
+        channel_state.panning = panning;
+        if let Some(track) = channel_state.track.as_mut() {
+            return command_result(
+                track.panning.set_panning(panning, tween.into()),
+                "set panning for channel",
+            );
+        }
+
+        AudioCommandResult::Ok
+    }
+
+    fn set_playback_rate(
+        &mut self,
+        channel: &TypeId,
+        playback_rate: f64,
+        tween: &AudioTween,
+    ) -> AudioCommandResult {
         if let Some(instances) = self.instances.get_mut(channel) {
             for instance in instances.iter_mut() {
-                if let Err(error) = instance.kira.set_volume(volume, Tween::default()) {
-                    error!("Failed to set volume for instance: {:?}", error);
+                let result = instance
+                    .kira
+                    .set_playback_rate(playback_rate * instance.playback_rate, tween.into());
+                if let AudioCommandResult::Retry =
+                    command_result(result, "set playback rate for instance")
+                {
+                    return AudioCommandResult::Retry;
                 }
             }
         }
-        if let Some(mut channel_state) = self.channels.get_mut(channel) {
-            channel_state.volume = volume;
+        self.channels.entry(*channel).or_default().playback_rate = playback_rate;
+
+        AudioCommandResult::Ok
+    }
+
+    fn set_effect_parameter(
+        &mut self,
+        channel: &TypeId,
+        effect: usize,
+        parameter: &EffectParameter,
+        tween: &AudioTween,
+    ) -> AudioCommandResult {
+        let effect_handle = self
+            .channels
+            .get_mut(channel)
+            .and_then(|channel_state| channel_state.track.as_mut())
+            .and_then(|track| track.effects.get_mut(effect));
+        if let Some(effect_handle) = effect_handle {
+            match effect_handle.set(parameter, tween.into()) {
+                Err(SetEffectParameterError::Command(CommandError::CommandQueueFull)) => {
+                    return AudioCommandResult::Retry;
+                }
+                Err(SetEffectParameterError::Command(error)) => {
+                    error!("Failed to set effect parameter: {:?}", error);
+                }
+                Err(SetEffectParameterError::UnsupportedParameter) => {
+                    error!(
+                        "Effect {} of the channel does not support {:?}",
+                        effect, parameter
+                    );
+                }
+                Ok(()) => (),
+            }
         } else {
-            let channel_state = ChannelState {
-                volume,
-                ..Default::default()
-            };
-            self.channels.insert(*channel, channel_state);
+            error!("The channel has no effect with index {}", effect);
         }
+
+        AudioCommandResult::Ok
+    }
+
+    fn instance_mut(
+        &mut self,
+        channel: &TypeId,
+        instance_handle: &InstanceHandle,
+    ) -> Option<&mut InstanceState> {
+        self.instances.get_mut(channel).and_then(|instances| {
+            instances
+                .iter_mut()
+                .find(|instance| &instance.handle == instance_handle)
+        })
     }
 
-    fn set_panning(&mut self, channel: &TypeId, panning: f64) {
+    fn stop_instance(
+        &mut self,
+        channel: &TypeId,
+        instance_handle: &InstanceHandle,
+        tween: &AudioTween,
+    ) -> AudioCommandResult {
+        if let Some(instance) = self.instance_mut(channel, instance_handle) {
+            return command_result(instance.stop(tween.into()), "stop instance");
+        }
+
+        AudioCommandResult::Ok
+    }
+
+    fn pause_instance(
+        &mut self,
+        channel: &TypeId,
+        instance_handle: &InstanceHandle,
+        tween: &AudioTween,
+    ) -> AudioCommandResult {
+        if let Some(instance) = self.instance_mut(channel, instance_handle) {
+            if kira::sound::static_sound::PlaybackState::Playing == instance.kira.state() {
+                return command_result(instance.kira.pause(tween.into()), "pause instance");
+            }
+        }
+
+        AudioCommandResult::Ok
+    }
+
+    fn resume_instance(
+        &mut self,
+        channel: &TypeId,
+        instance_handle: &InstanceHandle,
+        tween: &AudioTween,
+    ) -> AudioCommandResult {
+        if let Some(instance) = self.instance_mut(channel, instance_handle) {
+            if let kira::sound::static_sound::PlaybackState::Paused = instance.kira.state() {
+                return command_result(instance.kira.resume(tween.into()), "resume instance");
+            }
+        }
+
+        AudioCommandResult::Ok
+    }
+
+    fn set_instance_volume(
+        &mut self,
+        channel: &TypeId,
+        instance_handle: &InstanceHandle,
+        volume: f64,
+        tween: &AudioTween,
+    ) -> AudioCommandResult {
+        if let Some(instance) = self.instance_mut(channel, instance_handle) {
+            instance.volume = volume;
+            return command_result(
+                instance
+                    .kira
+                    .set_volume(instance.kira_volume(), tween.into()),
+                "set volume for instance",
+            );
+        }
+
+        AudioCommandResult::Ok
+    }
+
+    fn set_instance_panning(
+        &mut self,
+        channel: &TypeId,
+        instance_handle: &InstanceHandle,
+        panning: f64,
+        tween: &AudioTween,
+    ) -> AudioCommandResult {
+        if let Some(instance) = self.instance_mut(channel, instance_handle) {
+            if let Some(play_out) = instance.play_out.as_mut() {
+                play_out.sound.settings.panning = panning;
+            }
+            return command_result(
+                instance.kira.set_panning(panning, tween.into()),
+                "set panning for instance",
+            );
+        }
+
+        AudioCommandResult::Ok
+    }
+
+    fn set_instance_playback_rate(
+        &mut self,
+        channel: &TypeId,
+        instance_handle: &InstanceHandle,
+        playback_rate: f64,
+        tween: &AudioTween,
+    ) -> AudioCommandResult {
+        let channel_playback_rate = self
+            .channels
+            .get(channel)
+            .map_or(1.0, |state| state.playback_rate);
+        if let Some(instance) = self.instance_mut(channel, instance_handle) {
+            instance.playback_rate = playback_rate;
+            return command_result(
+                instance
+                    .kira
+                    .set_playback_rate(channel_playback_rate * playback_rate, tween.into()),
+                "set playback rate for instance",
+            );
+        }
+
+        AudioCommandResult::Ok
+    }
+
+    fn seek_to(&mut self, channel: &TypeId, instance_handle: &InstanceHandle, position: f64) {
+        if let Some(instance) = self.instance_mut(channel, instance_handle) {
+            instance.last_position = None;
+            if let Err(error) = instance.kira.seek_to(position) {
+                error!("Failed to seek instance: {:?}", error);
+            }
+        }
+    }
+
+    fn seek_by(&mut self, channel: &TypeId, instance_handle: &InstanceHandle, delta: f64) {
+        if let Some(instance) = self.instance_mut(channel, instance_handle) {
+            instance.last_position = None;
+            if let Err(error) = instance.kira.seek_by(delta) {
+                error!("Failed to seek instance: {:?}", error);
+            }
+        }
+    }
+
+    fn play(
+        &mut self,
+        channel: &TypeId,
+        play_settings: &PlayAudioSettings,
+        audio_source: &AudioSource,
+        intro_source: Option<&AudioSource>,
+        instance_handle: InstanceHandle,
+    ) -> AudioCommandResult {
+        if !self.enforce_instance_limit(channel, play_settings.priority) {
+            warn!(
+                "Failed to play sound: the maximum number of instances is playing in the channel"
+            );
+            self.mark_failed(channel, instance_handle);
+            return AudioCommandResult::Ok;
+        }
+        let mut settings = audio_source.sound.settings();
+        play_settings.apply(&mut settings);
+        let volume = settings.volume.as_amplitude();
+        let playback_rate = settings.playback_rate.as_factor();
+        if let Some(channel_state) = self.channels.get(channel) {
+            channel_state.apply(&mut settings);
+        }
+        if play_settings.crossfade.is_some() {
+            settings.volume = Volume::Amplitude(0.);
+        }
+        let mut play_out = None;
+        let mut end = None;
+        let sound_handle = match &audio_source.sound {
+            AudioSourceData::Static(sound) => {
+                let mut sound = StaticSoundData {
+                    settings,
+                    ..sound.clone()
+                };
+                let loops = settings.loop_behavior.is_some();
+                let full_sound = sound.clone();
+                if let Some(loop_end) = play_settings.loop_end.or(audio_source.loop_end) {
+                    if loops {
+                        self.end_loop_at(&mut sound, play_settings.source.id, loop_end);
+                    }
+                }
+                let looped_frames = sound.frames.len();
+                if let Some(intro_source) = intro_source {
+                    self.prepend_intro(&mut sound, play_settings, intro_source);
+                }
+                if loops {
+                    play_out = Some(PlayOut {
+                        sound: full_sound,
+                        intro_duration: (sound.frames.len() - looped_frames) as f64
+                            / sound.sample_rate as f64,
+                    });
+                }
+                end = Some(sound.duration().as_secs_f64());
+                match self.manager.as_mut().unwrap().play(sound) {
+                    Ok(handle) => SoundHandle::Static(handle),
+                    Err(error) => {
+                        return self.play_failed(
+                            channel,
+                            instance_handle,
+                            play_settings.priority,
+                            error,
+                        )
+                    }
+                }
+            }
+            #[cfg(all(
+                any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+                not(target_arch = "wasm32")
+            ))]
+            AudioSourceData::Streaming { bytes, .. } => {
+                if intro_source.is_some() {
+                    warn!("Streamed sounds cannot be played with an intro. Only the looped sound will play.");
+                }
+                if settings.reverse {
+                    warn!("Streamed sounds cannot be played in reverse.");
+                }
+                let sound = match StreamingSoundData::from_cursor(
+                    Cursor::new(bytes.clone()),
+                    streaming_settings(&settings),
+                ) {
+                    Ok(sound) => sound,
+                    Err(error) => {
+                        error!("Failed to stream sound: {}", error);
+                        self.mark_failed(channel, instance_handle);
+                        return AudioCommandResult::Ok;
+                    }
+                };
+                match self.manager.as_mut().unwrap().play(sound) {
+                    Ok(handle) => SoundHandle::Streaming(handle),
+                    Err(error) => {
+                        return self.play_failed(
+                            channel,
+                            instance_handle,
+                            play_settings.priority,
+                            error,
+                        )
+                    }
+                }
+            }
+            AudioSourceData::Group(_) => {
+                error!("Failed to play sound: variants of sound groups cannot be groups");
+                self.mark_failed(channel, instance_handle);
+                return AudioCommandResult::Ok;
+            }
+        };
+        let instance_state = InstanceState {
+            kira: sound_handle,
+            handle: instance_handle,
+            volume,
+            attenuation: 1.0,
+            source_attenuation: audio_source.attenuation.clone(),
+            playback_rate,
+            panning: None,
+            fade: if play_settings.crossfade.is_some() {
+                0.
+            } else {
+                1.
+            },
+            fading_out: false,
+            stopped: false,
+            scheduled: play_settings.start_time.is_some(),
+            stop_scheduled: false,
+            loops: settings.loop_behavior.is_some(),
+            play_out,
+            reverse: settings.reverse,
+            last_position: None,
+            start_order: self.started_instances,
+            priority: play_settings.priority,
+            tempo: audio_source.tempo,
+            end,
+        };
+        self.started_instances += 1;
+        self.events.push(InstanceEvent::Started(AudioStarted {
+            channel: *channel,
+            handle: instance_state.handle.clone(),
+        }));
+        let instance_handle = instance_state.handle.clone();
+        if let Some(instance_states) = self.instances.get_mut(channel) {
+            instance_states.push(instance_state);
+        } else {
+            self.instances.insert(*channel, vec![instance_state]);
+        }
+        if let Some(duration) = play_settings.crossfade {
+            self.start_crossfade(channel, instance_handle, duration);
+        }
+
+        AudioCommandResult::Ok
+    }
+
+    /// Fade out all other instances of the channel while fading in the given one
+    ///
+    /// Running crossfades are replaced. Their instances fade out from their current volume.
+    fn start_crossfade(&mut self, channel: &TypeId, incoming: InstanceHandle, duration: Duration) {
+        let mut outgoing = vec![];
         if let Some(instances) = self.instances.get_mut(channel) {
             for instance in instances.iter_mut() {
-                if let Err(error) = instance.kira.set_panning(panning, Tween::default()) {
-                    error!("Failed to set panning for instance: {:?}", error);
+                if instance.handle != incoming && instance.is_active() {
+                    instance.fading_out = true;
+                    outgoing.push((instance.handle.clone(), instance.fade));
+                }
+            }
+        }
+        self.channels.entry(*channel).or_default().crossfades = vec![Crossfade {
+            incoming,
+            outgoing,
+            duration,
+            elapsed: Duration::ZERO,
+        }];
+    }
+
+    /// Progress the crossfades of the channel
+    ///
+    /// The volumes follow an equal-power curve, keeping the perceived loudness constant.
+    /// Faded out instances are stopped at the end of the crossfade.
+    fn update_crossfades(&mut self, channel: &TypeId, delta: Duration) {
+        let crossfades = match self.channels.get_mut(channel) {
+            Some(channel_state) => std::mem::take(&mut channel_state.crossfades),
+            None => return,
+        };
+        let mut running = vec![];
+        for mut crossfade in crossfades {
+            crossfade.elapsed += delta;
+            let progress = if crossfade.duration.is_zero() {
+                1.
+            } else {
+                (crossfade.elapsed.as_secs_f64() / crossfade.duration.as_secs_f64()).min(1.)
+            };
+            let angle = progress * std::f64::consts::FRAC_PI_2;
+            if let Some(instance) = self.instance_mut(channel, &crossfade.incoming) {
+                instance.set_fade(angle.sin());
+            }
+            for (instance_handle, start_fade) in crossfade.outgoing.iter() {
+                if let Some(instance) = self.instance_mut(channel, instance_handle) {
+                    if progress < 1. {
+                        instance.set_fade(start_fade * angle.cos());
+                    } else if let Err(error) = instance.stop(Tween::default()) {
+                        error!("Failed to stop crossfaded instance: {:?}", error);
+                    }
+                }
+            }
+            if progress < 1. {
+                running.push(crossfade);
+            }
+        }
+        self.channels.entry(*channel).or_default().crossfades = running;
+    }
+
+    /// Handle an error of Kira when playing a sound
+    fn play_failed<E: Debug>(
+        &mut self,
+        channel: &TypeId,
+        instance_handle: InstanceHandle,
+        priority: i32,
+        error: PlaySoundError<E>,
+    ) -> AudioCommandResult {
+        match error {
+            PlaySoundError::CommandError(CommandError::CommandQueueFull) => {
+                AudioCommandResult::Retry
+            }
+            PlaySoundError::SoundLimitReached => {
+                self.sound_limit_reached(channel, instance_handle, priority)
+            }
+            error => {
+                error!("Failed to play sound: {:?}", error);
+                self.mark_failed(channel, instance_handle);
+                AudioCommandResult::Ok
+            }
+        }
+    }
+
+    /// Make room for a new instance in a channel with an instance limit
+    ///
+    /// Returns `false` if the new instance should not be played.
+    fn enforce_instance_limit(&mut self, channel: &TypeId, priority: i32) -> bool {
+        let channel_state = match self.channels.get(channel) {
+            Some(channel_state) => channel_state,
+            None => return true,
+        };
+        let limit = match channel_state.instance_limit {
+            Some(limit) => limit,
+            None => return true,
+        };
+        let channel_volume = channel_state.volume;
+        let instances = match self.instances.get_mut(channel) {
+            Some(instances) => instances,
+            None => return true,
+        };
+        let active_instances = instances
+            .iter()
+            .filter(|instance| instance.is_active())
+            .count();
+        if active_instances < limit.max_instances {
+            return true;
+        }
+        let candidates = instances
+            .iter_mut()
+            .map(|instance| (channel_volume, instance));
+        match select_victim(candidates, limit.policy, priority) {
+            Some(victim) => {
+                if let Err(error) = victim.stop(Tween::default()) {
+                    error!("Failed to stop instance for new sound: {:?}", error);
                 }
+                true
             }
+            None => false,
+        }
+    }
+
+    /// Apply the sound limit policy when Kira cannot play more sounds
+    fn sound_limit_reached(
+        &mut self,
+        channel: &TypeId,
+        instance_handle: InstanceHandle,
+        priority: i32,
+    ) -> AudioCommandResult {
+        // Stopped sounds free their place in Kira shortly, unless they wait for a clock
+        let stopping = self
+            .instances
+            .values()
+            .flatten()
+            .any(|instance| !instance.is_active() && !instance.is_scheduled());
+        if self.sound_limit_policy != SoundLimitPolicy::DropNew && stopping {
+            return AudioCommandResult::Retry;
+        }
+        let channels = &self.channels;
+        let candidates = self.instances.iter_mut().flat_map(|(channel, instances)| {
+            let channel_volume = channels.get(channel).map_or(1., |channel| channel.volume);
+            instances
+                .iter_mut()
+                .map(move |instance| (channel_volume, instance))
+        });
+        let victim = select_victim(candidates, self.sound_limit_policy, priority);
+        if let Some(victim) = victim {
+            if let Err(error) = victim.stop(Tween::default()) {
+                error!("Failed to stop instance for new sound: {:?}", error);
+            }
+            return AudioCommandResult::Retry;
+        }
+        warn!("Failed to play sound: the maximum number of sounds is playing");
+        self.mark_failed(channel, instance_handle);
+        AudioCommandResult::Ok
+    }
+
+    /// Stop and forget the started layers of layered music that could not start completely
+    ///
+    /// Events of the layers sent since `events` are dropped.
+    fn cancel_layers(&mut self, channel: &TypeId, layers: &[PlayAudioCommandArgs], events: usize) {
+        let is_layer = |instance_handle: &InstanceHandle| {
+            layers
+                .iter()
+                .any(|layer| &layer.instance_handle == instance_handle)
+        };
+        if let Some(instances) = self.instances.get_mut(channel) {
+            for instance in instances
+                .iter_mut()
+                .filter(|instance| is_layer(&instance.handle))
+            {
+                if let Err(error) = instance.stop(Tween::default()) {
+                    error!("Failed to stop music layer: {:?}", error);
+                }
+            }
+            instances.retain(|instance| !is_layer(&instance.handle));
+        }
+        if let Some(discarded) = self.discarded.get_mut(channel) {
+            discarded.retain(|(instance_handle, _)| !is_layer(instance_handle));
+        }
+        self.events.truncate(events);
+    }
+
+    /// Report the instance as failed
+    fn mark_failed(&mut self, channel: &TypeId, instance_handle: InstanceHandle) {
+        self.discard(channel, instance_handle.clone(), PlaybackState::Failed);
+        self.events.push(InstanceEvent::PlayFailed(AudioPlayFailed {
+            channel: *channel,
+            handle: instance_handle,
+        }));
+    }
+
+    /// Remember the state of an instance that will not play
+    fn discard(&mut self, channel: &TypeId, instance_handle: InstanceHandle, state: PlaybackState) {
+        let discarded = self.discarded.entry(*channel).or_default();
+        if discarded.len() >= MAX_DISCARDED_INSTANCES {
+            discarded.pop_back();
+        }
+        discarded.push_front((instance_handle, state));
+    }
+
+    /// Check if the cooldown allows playing the source again
+    fn cooldown_allows(
+        &mut self,
+        channel: &TypeId,
+        source: HandleId,
+        cooldown: PlayCooldown,
+    ) -> bool {
+        let channel_state = self.channels.entry(*channel).or_default();
+        let now = channel_state.elapsed;
+        let recent_plays = channel_state.recent_plays.entry(source).or_default();
+        while matches!(recent_plays.back(), Some(start) if now.saturating_sub(*start) >= cooldown.window)
+        {
+            recent_plays.pop_back();
+        }
+        recent_plays.len() < cooldown.max_plays
+    }
+
+    /// Remember the start of a source for its cooldown
+    fn record_play(&mut self, channel: &TypeId, source: HandleId) {
+        let channel_state = self.channels.entry(*channel).or_default();
+        let now = channel_state.elapsed;
+        channel_state
+            .recent_plays
+            .entry(source)
+            .or_default()
+            .push_front(now);
+    }
+
+    /// Cut the sound at the end of its loop region
+    ///
+    /// Kira always loops at the end of a sound, so the frames after the loop end are removed.
+    fn end_loop_at(&mut self, sound: &mut StaticSoundData, source: HandleId, loop_end: f64) {
+        if sound.settings.reverse {
+            warn!("Loop ends are ignored for sounds playing in reverse.");
+            return;
         }
-        if let Some(mut channel_state) = self.channels.get_mut(channel) {
-            channel_state.panning = panning;
-        } else {
-            let channel_state = ChannelState {
-                panning,
-                ..Default::default()
-            };
-            self.channels.insert(*channel, channel_state);
+        let end_frame = (loop_end.max(0.) * sound.sample_rate as f64).round() as usize;
+        if end_frame >= sound.frames.len() {
+            return;
+        }
+        let loop_start = sound
+            .settings
+            .loop_behavior
+            .map_or(0., |loop_behavior| loop_behavior.start_position);
+        if loop_end <= loop_start {
+            warn!(
+                "The loop end {} is not after the loop start {}. The sound loops at its end.",
+                loop_end, loop_start
+            );
+            return;
+        }
+        let loop_region = self
+            .loop_regions
+            .entry((source, end_frame))
+            .or_insert_with(|| LoopRegion::new(sound, end_frame));
+        if !loop_region.is_built_from(sound) {
+            *loop_region = LoopRegion::new(sound, end_frame);
         }
+        sound.frames = loop_region.frames.clone();
     }
 
-    fn set_playback_rate(&mut self, channel: &TypeId, playback_rate: f64) {
-        if let Some(instances) = self.instances.get_mut(channel) {
-            for instance in instances.iter_mut() {
-                if let Err(error) = instance
-                    .kira
-                    .set_playback_rate(playback_rate, Tween::default())
-                {
-                    error!("Failed to set playback rate for instance: {:?}", error);
+    /// Replace a looping instance with a non-looping sound at the same position
+    fn disable_looping(
+        &mut self,
+        channel: &TypeId,
+        instance_handle: &InstanceHandle,
+    ) -> AudioCommandResult {
+        let channel_playback_rate = self
+            .channels
+            .get(channel)
+            .map_or(1.0, |state| state.playback_rate);
+        let (sound, paused) = match self.instance_mut(channel, instance_handle) {
+            Some(instance) if instance.loops => {
+                let play_out = match instance.play_out.as_ref() {
+                    Some(play_out) => play_out,
+                    None => {
+                        warn!("Streamed sounds cannot stop looping.");
+                        return AudioCommandResult::Ok;
+                    }
+                };
+                let position = instance.kira.position() - play_out.intro_duration;
+                if position < 0. {
+                    // still playing the intro
+                    return AudioCommandResult::Retry;
+                }
+                let mut sound = play_out.sound.clone();
+                sound.settings.loop_behavior = None;
+                sound.settings.start_position = if sound.settings.reverse {
+                    sound.duration().as_secs_f64() - position
+                } else {
+                    position
+                };
+                sound.settings.volume = Volume::Amplitude(instance.kira_volume());
+                sound.settings.playback_rate =
+                    PlaybackRate::Factor(channel_playback_rate * instance.playback_rate);
+                if let Some(panning) = instance.panning {
+                    sound.settings.panning = panning;
                 }
+                sound.settings.fade_in_tween = Some(Tween::default());
+                sound.settings.start_time = StartTime::Immediate;
+                let paused = matches!(
+                    instance.kira.state(),
+                    kira::sound::static_sound::PlaybackState::Paused
+                        | kira::sound::static_sound::PlaybackState::Pausing
+                );
+                (sound, paused)
+            }
+            _ => return AudioCommandResult::Ok,
+        };
+        let end = sound.duration().as_secs_f64();
+        let mut handle = match self.manager.as_mut().unwrap().play(sound) {
+            Ok(handle) => handle,
+            Err(PlaySoundError::CommandError(CommandError::CommandQueueFull)) => {
+                return AudioCommandResult::Retry
+            }
+            Err(error) => {
+                error!("Failed to stop looping instance: {:?}", error);
+                return AudioCommandResult::Ok;
+            }
+        };
+        if paused {
+            if let Err(error) = handle.pause(Tween::default()) {
+                error!("Failed to pause instance: {:?}", error);
             }
         }
-        if let Some(mut channel_state) = self.channels.get_mut(channel) {
-            channel_state.playback_rate = playback_rate;
-        } else {
-            let channel_state = ChannelState {
-                playback_rate,
-                ..Default::default()
-            };
-            self.channels.insert(*channel, channel_state);
+        if let Some(instance) = self.instance_mut(channel, instance_handle) {
+            let mut looped = std::mem::replace(&mut instance.kira, SoundHandle::Static(handle));
+            if let Err(error) = looped.stop(Tween::default()) {
+                error!("Failed to stop looped sound: {:?}", error);
+            }
+            instance.loops = false;
+            instance.end = Some(end);
+            instance.play_out = None;
+            instance.last_position = None;
         }
+
+        AudioCommandResult::Ok
     }
 
-    fn play(
+    /// Play the intro once before the looped part of the sound
+    ///
+    /// The intro and the looped sound are combined into a single sound, so that the loop starts
+    /// sample accurate after the intro. The loop position is shifted by the length of the intro,
+    /// all other positions are relative to the start of the intro.
+    fn prepend_intro(
         &mut self,
-        channel: &TypeId,
+        sound: &mut StaticSoundData,
         play_settings: &PlayAudioSettings,
-        audio_source: &AudioSource,
-        instance_handle: InstanceHandle,
-    ) -> AudioCommandResult {
-        let mut sound = audio_source.sound.clone();
-        if let Some(channel_state) = self.channels.get(channel) {
-            channel_state.apply(&mut sound);
+        intro_source: &AudioSource,
+    ) {
+        let intro = match &intro_source.sound {
+            AudioSourceData::Static(intro) => intro,
+            #[cfg(all(
+                any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+                not(target_arch = "wasm32")
+            ))]
+            AudioSourceData::Streaming { .. } => {
+                warn!(
+                    "Streamed sounds cannot be played as intro. Only the looped sound will play."
+                );
+                return;
+            }
+            AudioSourceData::Group(_) => {
+                warn!("Sound groups cannot be played as intro. Only the looped sound will play.");
+                return;
+            }
+        };
+        if intro.sample_rate != sound.sample_rate {
+            warn!(
+                "Cannot play intro with sample rate {} before a sound with sample rate {}. Only the looped sound will play.",
+                intro.sample_rate, sound.sample_rate
+            );
+            return;
         }
-        if play_settings.looped && sound.settings.loop_behavior.is_none() {
-            sound.settings.loop_behavior = Some(LoopBehavior {
-                start_position: 0.0,
-            });
+        let key = (
+            play_settings
+                .intro_source
+                .as_ref()
+                .expect("Intro source handle for intro sound")
+                .id,
+            play_settings.source.id,
+        );
+        let intro_sound = self
+            .intro_sounds
+            .entry(key)
+            .or_insert_with(|| IntroSound::new(intro, sound));
+        if !intro_sound.is_built_from(intro, sound) {
+            *intro_sound = IntroSound::new(intro, sound);
         }
-        let sound_handle = self
-            .manager
-            .as_mut()
-            .unwrap()
-            .play(sound)
-            .expect("Failed to play sound");
-        let instance_state = InstanceState {
-            kira: sound_handle,
-            handle: instance_handle,
-        };
-        if let Some(instance_states) = self.instances.get_mut(channel) {
-            instance_states.push(instance_state);
-        } else {
-            self.instances.insert(*channel, vec![instance_state]);
+        let intro_duration = intro.duration().as_secs_f64();
+        sound.frames = intro_sound.frames.clone();
+        if let Some(loop_behavior) = sound.settings.loop_behavior.as_mut() {
+            loop_behavior.start_position += intro_duration;
         }
-
-        AudioCommandResult::Ok
     }
 
     pub(crate) fn play_channel<T: Resource>(
         &mut self,
         audio_sources: &Assets<AudioSource>,
         channel: &AudioChannel<T>,
+        delta: Duration,
     ) {
         if self.manager.is_none() {
             return;
         }
+        let channel_id = TypeId::of::<T>();
+        let channel_state = self.channels.entry(channel_id).or_default();
+        channel_state.instance_limit = channel.instance_limit;
+        channel_state.elapsed += delta;
+        channel_state
+            .recent_plays
+            .retain(|_, recent_plays| !recent_plays.is_empty());
+        self.update_crossfades(&channel_id, delta);
         let mut commands = channel.commands.write();
         let len = commands.len();
-        let channel_id = TypeId::of::<T>();
         let mut i = 0;
         while i < len {
-            let audio_command = commands.pop_back().unwrap();
-            let result = self.run_audio_command(&audio_command, audio_sources, &channel_id);
+            let mut audio_command = commands.pop_back().unwrap();
+            i += 1;
+            if let Some(start_delay) = audio_command.start_delay_mut() {
+                if !start_delay.is_zero() {
+                    *start_delay = start_delay.saturating_sub(delta);
+                    commands.push_front(audio_command);
+                    continue;
+                }
+            }
+            if let AudioCommand::Play(play_args) = &mut audio_command {
+                if let Some(quantize) = play_args.settings.quantize.as_mut() {
+                    match self.quantized_start_time(quantize) {
+                        Some(start_time) => play_args.settings.start_time = Some(start_time),
+                        // start in the frame closest to the grid position
+                        None if self.time_until_quantized_start(quantize)
+                            > delta.as_secs_f64() / 2. =>
+                        {
+                            commands.push_front(audio_command);
+                            continue;
+                        }
+                        None => {}
+                    }
+                }
+            }
+            // The targeted instance might still wait for its audio source to load
+            let waiting_for_play = audio_command
+                .target_instance()
+                .map(|instance_handle| {
+                    commands
+                        .iter()
+                        .any(|command| command.plays(instance_handle))
+                })
+                .unwrap_or(false);
+            let cooldown = match &audio_command {
+                AudioCommand::Play(play_args) => channel.cooldown(&play_args.settings.source),
+                _ => None,
+            };
+            let result = if waiting_for_play {
+                AudioCommandResult::Retry
+            } else {
+                self.run_audio_command(&audio_command, audio_sources, &channel_id, cooldown)
+            };
             if let AudioCommandResult::Retry = result {
                 commands.push_front(audio_command);
             }
-            i += 1;
+        }
+    }
+
+    /// Clock time at which a quantized play reaches its grid position
+    ///
+    /// Returns `None` if the play has to wait for its grid position in the command queue: if
+    /// the reference instance does not play a static sound or the clock is not running yet.
+    fn quantized_start_time(&mut self, start: &mut QuantizedStart) -> Option<ClockTime> {
+        let static_reference = self.instances.values().flatten().any(|instance| {
+            instance.handle == start.reference.instance
+                && matches!(instance.kira, SoundHandle::Static(_))
+        });
+        if !static_reference {
+            return None;
+        }
+        if self.quantize_clock.is_none() {
+            let clock = match self
+                .manager
+                .as_mut()?
+                .add_clock(ClockSpeed::TicksPerSecond(QUANTIZE_CLOCK_TICKS_PER_SECOND))
+            {
+                Ok(clock) => clock,
+                Err(error) => {
+                    warn!("Failed to add clock for quantized plays: {:?}", error);
+                    return None;
+                }
+            };
+            if let Err(error) = clock.start() {
+                error!("Failed to start clock for quantized plays: {:?}", error);
+            }
+            self.quantize_clock = Some(clock);
+        }
+        let time_until_start = self.time_until_quantized_start(start);
+        let clock = self.quantize_clock.as_ref()?;
+        if !clock.ticking() {
+            return None;
+        }
+        // the clock time was reached a fraction of a tick ago
+        let ticks =
+            time_until_start * QUANTIZE_CLOCK_TICKS_PER_SECOND + clock.fractional_position();
+        Some(clock.time() + (ticks.round() as u64).max(1))
+    }
+
+    /// Seconds until a quantized play reaches its grid position
+    ///
+    /// Returns 0 if the reference instance is not playing or has no tempo.
+    fn time_until_quantized_start(&self, start: &mut QuantizedStart) -> f64 {
+        let reference = self.instances.iter().find_map(|(channel, instances)| {
+            instances
+                .iter()
+                .find(|instance| instance.handle == start.reference.instance)
+                .map(|instance| (channel, instance))
+        });
+        let (channel, instance) = match reference {
+            Some(reference) => reference,
+            None => return 0.,
+        };
+        let tempo = match start.reference.tempo.or(instance.tempo) {
+            Some(tempo) => tempo,
+            None => {
+                warn!("Quantize reference has no tempo; the quantized play starts right away");
+                return 0.;
+            }
+        };
+        match PlaybackState::from(instance) {
+            PlaybackState::Playing { position } => {
+                let channel_playback_rate = self
+                    .channels
+                    .get(channel)
+                    .map_or(1., |channel_state| channel_state.playback_rate);
+                start.time_until_start(
+                    &tempo,
+                    position,
+                    instance.playback_rate * channel_playback_rate,
+                )
+            }
+            _ => 0.,
         }
     }
 
@@ -208,51 +1460,238 @@ impl AudioOutput {
         audio_command: &AudioCommand,
         audio_sources: &Assets<AudioSource>,
         channel: &TypeId,
+        cooldown: Option<PlayCooldown>,
     ) -> AudioCommandResult {
         match audio_command {
             AudioCommand::Play(play_args) => {
-                if let Some(audio_source) = audio_sources.get(&play_args.settings.source) {
-                    self.play(
-                        channel,
-                        &play_args.settings,
-                        audio_source,
-                        play_args.instance_handle.clone(),
-                    )
+                let intro_source = play_args
+                    .settings
+                    .intro_source
+                    .as_ref()
+                    .map(|intro| audio_sources.get(intro));
+                if let (Some(audio_source), None | Some(Some(_))) =
+                    (audio_sources.get(&play_args.settings.source), intro_source)
+                {
+                    let source = play_args.settings.source.id;
+                    if let Some(cooldown) = cooldown {
+                        if !self.cooldown_allows(channel, source, cooldown) {
+                            self.discard(
+                                channel,
+                                play_args.instance_handle.clone(),
+                                PlaybackState::Suppressed,
+                            );
+                            return AudioCommandResult::Ok;
+                        }
+                    }
+                    let started_instances = self.started_instances;
+                    let result = if let AudioSourceData::Group(group) = &audio_source.sound {
+                        if !group.is_loaded(audio_sources) {
+                            return AudioCommandResult::Retry;
+                        }
+                        if group.variants.is_empty() {
+                            warn!("Failed to play sound group: the group has no variants");
+                            self.mark_failed(channel, play_args.instance_handle.clone());
+                            return AudioCommandResult::Ok;
+                        }
+                        let variant = self.group_playbacks.entry(source).or_default().pick(group);
+                        let settings = group.vary(&play_args.settings, variant);
+                        self.play(
+                            channel,
+                            &settings,
+                            audio_sources.get(&settings.source).unwrap(),
+                            intro_source.flatten(),
+                            play_args.instance_handle.clone(),
+                        )
+                    } else {
+                        self.play(
+                            channel,
+                            &play_args.settings,
+                            audio_source,
+                            intro_source.flatten(),
+                            play_args.instance_handle.clone(),
+                        )
+                    };
+                    // dropped and failed plays do not count for the cooldown
+                    if cooldown.is_some() && self.started_instances > started_instances {
+                        self.record_play(channel, source);
+                    }
+                    result
                 } else {
-                    // audio source hasn't loaded yet. Add it back to the queue
+                    // audio sources haven't loaded yet. Add it back to the queue
                     AudioCommandResult::Retry
                 }
             }
-            AudioCommand::Stop => self.stop(channel),
-            AudioCommand::Pause => {
-                self.pause(channel);
+            AudioCommand::PlayLayered(layers) => {
+                let loaded =
+                    layers
+                        .iter()
+                        .all(|layer| match audio_sources.get(&layer.settings.source) {
+                            Some(AudioSource {
+                                sound: AudioSourceData::Group(group),
+                                ..
+                            }) => group.is_loaded(audio_sources),
+                            Some(_) => true,
+                            None => false,
+                        });
+                if !loaded {
+                    return AudioCommandResult::Retry;
+                }
+                let clock = match self
+                    .manager
+                    .as_mut()
+                    .unwrap()
+                    .add_clock(ClockSpeed::TicksPerSecond(START_CLOCK_TICKS_PER_SECOND))
+                {
+                    Ok(clock) => Some(clock),
+                    Err(error) => {
+                        warn!("Failed to synchronize music layers: {:?}", error);
+                        None
+                    }
+                };
+                let events = self.events.len();
+                for layer in layers.iter() {
+                    let mut settings = layer.settings.clone();
+                    settings.start_time = clock.as_ref().map(|clock| ClockTime {
+                        ticks: 0,
+                        ..clock.time()
+                    });
+                    let play = AudioCommand::Play(Box::new(PlayAudioCommandArgs {
+                        settings,
+                        instance_handle: layer.instance_handle.clone(),
+                    }));
+                    let started_instances = self.started_instances;
+                    let result = self.run_audio_command(&play, audio_sources, channel, None);
+                    if self.started_instances == started_instances {
+                        // the stems are only synchronized if all of them start together
+                        self.cancel_layers(channel, layers, events);
+                        if let AudioCommandResult::Ok = result {
+                            warn!("Failed to play music layers: a layer failed to play");
+                            for layer in layers.iter() {
+                                self.mark_failed(channel, layer.instance_handle.clone());
+                            }
+                        }
+                        return result;
+                    }
+                }
+                if let Some(clock) = clock {
+                    if let Err(error) = clock.start() {
+                        error!("Failed to start music layers: {:?}", error);
+                    }
+                    self.start_clocks.push(clock);
+                }
                 AudioCommandResult::Ok
             }
-            AudioCommand::Resume => {
-                self.resume(channel);
-                AudioCommandResult::Ok
+            AudioCommand::Stop(tween) => self.stop(channel, tween),
+            AudioCommand::Pause(tween) => self.pause(channel, tween),
+            AudioCommand::Resume(tween) => self.resume(channel, tween),
+            AudioCommand::SetVolume(volume, tween) => {
+                self.set_volume(channel, *volume as f64, tween)
             }
-            AudioCommand::SetVolume(volume) => {
-                self.set_volume(channel, *volume as f64);
-                AudioCommandResult::Ok
+            AudioCommand::SetPanning(panning, tween) => {
+                self.set_panning(channel, *panning as f64, tween)
+            }
+            AudioCommand::SetPlaybackRate(playback_rate, tween) => {
+                self.set_playback_rate(channel, *playback_rate as f64, tween)
+            }
+            AudioCommand::StopInstance(instance_handle, tween) => {
+                self.stop_instance(channel, instance_handle, tween)
+            }
+            AudioCommand::PauseInstance(instance_handle, tween) => {
+                self.pause_instance(channel, instance_handle, tween)
+            }
+            AudioCommand::ResumeInstance(instance_handle, tween) => {
+                self.resume_instance(channel, instance_handle, tween)
+            }
+            AudioCommand::SetInstanceVolume(instance_handle, volume, tween) => {
+                self.set_instance_volume(channel, instance_handle, *volume as f64, tween)
+            }
+            AudioCommand::SetInstancePanning(instance_handle, panning, tween) => {
+                self.set_instance_panning(channel, instance_handle, *panning as f64, tween)
+            }
+            AudioCommand::SetInstancePlaybackRate(instance_handle, playback_rate, tween) => self
+                .set_instance_playback_rate(channel, instance_handle, *playback_rate as f64, tween),
+            AudioCommand::SetEffectParameter(effect, parameter, tween) => {
+                self.set_effect_parameter(channel, *effect, parameter, tween)
             }
-            AudioCommand::SetPanning(panning) => {
-                self.set_panning(channel, *panning as f64);
+            AudioCommand::SeekTo(instance_handle, position) => {
+                self.seek_to(channel, instance_handle, *position);
                 AudioCommandResult::Ok
             }
-            AudioCommand::SetPlaybackRate(playback_rate) => {
-                self.set_playback_rate(channel, *playback_rate as f64);
+            AudioCommand::SeekBy(instance_handle, delta) => {
+                self.seek_by(channel, instance_handle, *delta);
                 AudioCommandResult::Ok
             }
+            AudioCommand::DisableLooping(instance_handle) => {
+                self.disable_looping(channel, instance_handle)
+            }
         }
     }
 
-    pub(crate) fn cleanup_stopped_instances(&mut self) {
-        for (_, instances) in self.instances.iter_mut() {
+    /// Remove stopped instances and collect the lifecycle events of all instances
+    pub(crate) fn cleanup_stopped_instances(&mut self) -> Vec<InstanceEvent> {
+        let mut events = std::mem::take(&mut self.events);
+        for (channel, instances) in self.instances.iter_mut() {
+            for instance in instances.iter_mut() {
+                instance.kira.log_errors();
+                instance.update_scheduled();
+                if instance.has_looped() {
+                    events.push(InstanceEvent::Looped(AudioLooped {
+                        channel: *channel,
+                        handle: instance.handle.clone(),
+                    }));
+                }
+            }
             instances.retain(|instance| {
-                instance.kira.state() != kira::sound::static_sound::PlaybackState::Stopped
+                let stopped =
+                    instance.kira.state() == kira::sound::static_sound::PlaybackState::Stopped;
+                if stopped {
+                    events.push(InstanceEvent::Finished(AudioFinished {
+                        channel: *channel,
+                        handle: instance.handle.clone(),
+                    }));
+                }
+                !stopped
             });
         }
+        self.intro_sounds
+            .retain(|_, intro_sound| !intro_sound.sources_unloaded());
+        self.loop_regions
+            .retain(|_, loop_region| !loop_region.source_unloaded());
+        self.start_clocks.retain(|clock| clock.time().ticks == 0);
+
+        events
+    }
+}
+
+/// Select the instance to stop for a new sound with the given priority
+///
+/// Only active instances with at most the given priority that are not waiting for their start
+/// time are stolen. Instances with lower priority are stolen first. The candidates are paired
+/// with the volume of their channel.
+fn select_victim<'a>(
+    candidates: impl Iterator<Item = (f64, &'a mut InstanceState)>,
+    policy: SoundLimitPolicy,
+    priority: i32,
+) -> Option<&'a mut InstanceState> {
+    let candidates = candidates.filter(|(_, instance)| {
+        instance.is_active() && !instance.scheduled && instance.priority <= priority
+    });
+    match policy {
+        SoundLimitPolicy::DropNew => None,
+        SoundLimitPolicy::StealOldest => candidates
+            .min_by_key(|(_, instance)| (instance.priority, instance.start_order))
+            .map(|(_, instance)| instance),
+        SoundLimitPolicy::StealQuietest => candidates
+            .min_by(|(volume_a, a), (volume_b, b)| {
+                let loudness_a = volume_a * a.kira_volume();
+                let loudness_b = volume_b * b.kira_volume();
+                a.priority.cmp(&b.priority).then(
+                    loudness_a
+                        .partial_cmp(&loudness_b)
+                        .unwrap_or(std::cmp::Ordering::Equal),
+                )
+            })
+            .map(|(_, instance)| instance),
     }
 }
 
@@ -260,6 +1699,29 @@ struct ChannelState {
     volume: f64,
     playback_rate: f64,
     panning: f64,
+    instance_limit: Option<InstanceLimit>,
+    /// Time the channel has been playing, used for cooldowns
+    elapsed: Duration,
+    /// Start times of recently played sources, newest first
+    recent_plays: HashMap<HandleId, VecDeque<Duration>>,
+    crossfades: Vec<Crossfade>,
+    track: Option<ChannelTrack>,
+}
+
+/// A running crossfade from the outgoing instances to the incoming one
+struct Crossfade {
+    incoming: InstanceHandle,
+    /// Faded out instances with their volume multiplier at the start of the crossfade
+    outgoing: Vec<(InstanceHandle, f64)>,
+    duration: Duration,
+    elapsed: Duration,
+}
+
+/// The Kira mixer sub-track of a channel
+struct ChannelTrack {
+    track: TrackHandle,
+    panning: PanningControlHandle,
+    effects: Vec<EffectHandle>,
 }
 
 impl Default for ChannelState {
@@ -268,15 +1730,37 @@ impl Default for ChannelState {
             volume: 1.0,
             playback_rate: 1.0,
             panning: 0.5,
+            instance_limit: None,
+            elapsed: Duration::ZERO,
+            recent_plays: HashMap::default(),
+            crossfades: vec![],
+            track: None,
         }
     }
 }
 
 impl ChannelState {
-    pub(crate) fn apply(&self, sound: &mut StaticSoundData) {
-        sound.settings.volume = self.volume.into();
-        sound.settings.playback_rate = self.playback_rate.into();
-        sound.settings.panning = self.panning;
+    /// Play the sound on the channel's track with the channel's playback rate
+    ///
+    /// Volume and panning of the channel are applied by its track.
+    pub(crate) fn apply(&self, settings: &mut StaticSoundSettings) {
+        settings.playback_rate =
+            PlaybackRate::Factor(settings.playback_rate.as_factor() * self.playback_rate);
+        if let Some(track) = self.track.as_ref() {
+            settings.track = track.track.id();
+        }
+    }
+}
+
+/// Retry a command if the command queue of Kira is full and log other errors
+fn command_result(result: Result<(), CommandError>, action: &str) -> AudioCommandResult {
+    match result {
+        Err(CommandError::CommandQueueFull) => AudioCommandResult::Retry,
+        Err(error) => {
+            error!("Failed to {}: {:?}", action, error);
+            AudioCommandResult::Ok
+        }
+        Ok(()) => AudioCommandResult::Ok,
     }
 }
 
@@ -284,26 +1768,973 @@ pub(crate) fn play_audio_channel<T: Resource>(
     mut audio_output: NonSendMut<AudioOutput>,
     channel: Res<AudioChannel<T>>,
     audio_sources: Option<Res<Assets<AudioSource>>>,
+    time: Option<Res<Time>>,
 ) {
+    let delta = time.map(|time| time.delta()).unwrap_or_default();
     if let Some(audio_sources) = audio_sources {
-        audio_output.play_channel(&*audio_sources, &channel);
+        audio_output.play_channel(&audio_sources, &channel, delta);
     };
 }
 
-pub(crate) fn cleanup_stopped_instances(mut audio_output: NonSendMut<AudioOutput>) {
-    audio_output.cleanup_stopped_instances();
+pub(crate) fn cleanup_stopped_instances(
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut event_writers: InstanceEventWriters,
+) {
+    for event in audio_output.cleanup_stopped_instances() {
+        event_writers.send(event);
+    }
+}
+
+/// Forget the picked variants of sound groups that were removed
+pub(crate) fn cleanup_group_playbacks(
+    mut audio_output: NonSendMut<AudioOutput>,
+    mut asset_events: EventReader<AssetEvent<AudioSource>>,
+) {
+    for event in asset_events.iter() {
+        if let AssetEvent::Removed { handle } = event {
+            audio_output.group_playbacks.remove(&handle.id);
+        }
+    }
 }
 
 pub(crate) fn update_instance_states<T: Resource>(
     audio_output: NonSend<AudioOutput>,
     mut channel: ResMut<AudioChannel<T>>,
+    mut beats: EventWriter<AudioBeat>,
+    mut bars: EventWriter<AudioBar>,
 ) {
-    if let Some(instances) = audio_output.instances.get(&TypeId::of::<T>()) {
-        channel.states.clear();
+    let channel_id = TypeId::of::<T>();
+    let previous_states = std::mem::take(&mut channel.states);
+    channel.remaining_times.clear();
+    let channel_playback_rate = audio_output
+        .channels
+        .get(&channel_id)
+        .map_or(1.0, |state| state.playback_rate);
+    if let Some(instances) = audio_output.instances.get(&channel_id) {
         for instance_state in instances.iter() {
-            channel
-                .states
-                .insert(instance_state.handle.clone(), instance_state.into());
+            let state = PlaybackState::from(instance_state);
+            if let (PlaybackState::Playing { .. }, Some(remaining)) =
+                (state, instance_state.remaining(channel_playback_rate))
+            {
+                channel
+                    .remaining_times
+                    .insert(instance_state.handle.clone(), remaining);
+            }
+            if let (Some(tempo), Some(position)) = (instance_state.tempo, state.position()) {
+                let previous = previous_states
+                    .get(&instance_state.handle)
+                    .and_then(PlaybackState::position);
+                let beats_per_bar = u64::from(tempo.beats_per_bar.max(1));
+                for beat in tempo.reached_beats(previous, position) {
+                    let bar = beat / beats_per_bar;
+                    let beat_in_bar = (beat % beats_per_bar) as u32;
+                    if beat_in_bar == 0 {
+                        bars.send(AudioBar {
+                            channel: channel_id,
+                            handle: instance_state.handle.clone(),
+                            bar,
+                        });
+                    }
+                    beats.send(AudioBeat {
+                        channel: channel_id,
+                        handle: instance_state.handle.clone(),
+                        beat,
+                        bar,
+                        beat_in_bar,
+                    });
+                }
+            }
+            channel.states.insert(instance_state.handle.clone(), state);
+        }
+    }
+    if let Some(discarded) = audio_output.discarded.get(&channel_id) {
+        for (instance_handle, state) in discarded.iter() {
+            channel.states.insert(instance_handle.clone(), *state);
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::{AudioApp, LayeredMusic, MusicLayer, Quantize, QuantizeReference};
+
+    fn sound(frames: Vec<Frame>) -> StaticSoundData {
+        StaticSoundData {
+            sample_rate: 1,
+            frames: Arc::new(frames),
+            settings: StaticSoundSettings::default(),
+        }
+    }
+
+    #[test]
+    fn intro_sound_plays_intro_before_looped_sound() {
+        let intro = sound(vec![Frame::from_mono(1.); 2]);
+        let looped = sound(vec![Frame::from_mono(2.); 3]);
+
+        let intro_sound = IntroSound::new(&intro, &looped);
+
+        assert_eq!(
+            *intro_sound.frames,
+            vec![
+                Frame::from_mono(1.),
+                Frame::from_mono(1.),
+                Frame::from_mono(2.),
+                Frame::from_mono(2.),
+                Frame::from_mono(2.)
+            ]
+        );
+        assert!(intro_sound.is_built_from(&intro, &looped));
+        assert!(!intro_sound.is_built_from(&looped, &intro));
+        assert!(!intro_sound.sources_unloaded());
+
+        drop(intro);
+        assert!(intro_sound.sources_unloaded());
+    }
+
+    pub(crate) fn mock_output(settings: AudioSettings) -> AudioOutput {
+        let mut world = World::new();
+        world.insert_resource(AudioSettings {
+            backend: crate::AudioBackend::Mock { sample_rate: 100 },
+            ..settings
+        });
+        let mut audio_output = AudioOutput::from_world(&mut world);
+        audio_output.add_channel::<crate::MainTrack>(vec![]);
+        audio_output
+    }
+
+    /// A static sound with the sample rate of the mock output
+    pub(crate) fn source(frames: Vec<Frame>) -> AudioSource {
+        AudioSource::new(StaticSoundData {
+            sample_rate: 100,
+            ..sound(frames)
+        })
+    }
+
+    /// App with minimal plugins and audio source assets
+    pub(crate) fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(bevy::asset::AssetPlugin)
+            .add_asset::<AudioSource>();
+        app
+    }
+
+    fn play_settings() -> PlayAudioSettings {
+        PlayAudioSettings::new(Handle::weak(HandleId::default::<AudioSource>()))
+    }
+
+    #[test]
+    fn mock_backend_plays_sounds_when_ticked() {
+        let mut audio_output = mock_output(default());
+        let channel = TypeId::of::<crate::MainTrack>();
+        let source = source(vec![Frame::from_mono(1.); 50]);
+        let instance_handle = InstanceHandle::new();
+
+        audio_output.play(
+            &channel,
+            &play_settings(),
+            &source,
+            None,
+            instance_handle.clone(),
+        );
+        audio_output.tick(Duration::from_millis(250));
+
+        let state: crate::PlaybackState = (&audio_output.instances[&channel][0]).into();
+        assert!(matches!(state, crate::PlaybackState::Playing { .. }));
+        assert!((state.position().unwrap() - 0.25).abs() < 0.02);
+
+        audio_output.tick(Duration::from_millis(500));
+        let events = audio_output.cleanup_stopped_instances();
+
+        assert!(audio_output.instances[&channel].is_empty());
+        assert!(matches!(
+            events.last(),
+            Some(InstanceEvent::Finished(AudioFinished { handle, .. })) if handle == &instance_handle
+        ));
+    }
+
+    #[test]
+    fn channel_tracks_use_settings_inserted_after_the_channel() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<AudioBeat>()
+            .add_event::<AudioBar>()
+            .add_audio_channel::<crate::MainTrack>()
+            .insert_resource(AudioSettings {
+                backend: crate::AudioBackend::Mock { sample_rate: 100 },
+                ..default()
+            })
+            .init_non_send_resource::<AudioOutput>();
+
+        app.update();
+
+        let audio_output = app.world.non_send_resource::<AudioOutput>();
+        assert!(audio_output.channels[&TypeId::of::<crate::MainTrack>()]
+            .track
+            .is_some());
+    }
+
+    #[test]
+    fn mock_backend_renders_channel_mix() {
+        let mut audio_output = mock_output(default());
+        let channel = TypeId::of::<crate::MainTrack>();
+        let source = source(vec![Frame::from_mono(0.8); 10]);
+        let mut play_settings = play_settings();
+        play_settings.looped = true;
+
+        audio_output.play(
+            &channel,
+            &play_settings,
+            &source,
+            None,
+            InstanceHandle::new(),
+        );
+        audio_output.set_volume(&channel, 0.5, &AudioTween::default());
+        let recording = audio_output.render(Duration::from_secs(1)).unwrap();
+
+        assert_eq!(recording.sample_rate, 100);
+        assert_eq!(recording.frames.len(), 100);
+        for frame in recording.frames[10..].iter() {
+            assert!((frame.left - 0.4).abs() < 1e-4, "{:?}", frame);
+            assert!((frame.right - 0.4).abs() < 1e-4, "{:?}", frame);
+        }
+    }
+
+    #[test]
+    fn new_sounds_fail_when_sound_limit_is_reached() {
+        let mut audio_output = mock_output(AudioSettings {
+            sound_capacity: 1,
+            ..default()
+        });
+        let channel = TypeId::of::<crate::MainTrack>();
+        let source = source(vec![Frame::from_mono(1.); 50]);
+        let failing_instance = InstanceHandle::new();
+
+        audio_output.play(
+            &channel,
+            &play_settings(),
+            &source,
+            None,
+            InstanceHandle::new(),
+        );
+        let result = audio_output.play(
+            &channel,
+            &play_settings(),
+            &source,
+            None,
+            failing_instance.clone(),
+        );
+
+        assert!(matches!(result, AudioCommandResult::Ok));
+        assert_eq!(
+            audio_output.discarded[&channel],
+            vec![(failing_instance.clone(), PlaybackState::Failed)]
+        );
+        assert!(matches!(
+            audio_output.cleanup_stopped_instances().last(),
+            Some(InstanceEvent::PlayFailed(AudioPlayFailed { handle, .. })) if handle == &failing_instance
+        ));
+    }
+
+    #[test]
+    fn scheduled_instances_do_not_block_plays_at_the_sound_limit() {
+        let mut audio_output = mock_output(AudioSettings {
+            sound_capacity: 2,
+            sound_limit_policy: SoundLimitPolicy::StealOldest,
+            ..default()
+        });
+        let channel = TypeId::of::<crate::MainTrack>();
+        let source = source(vec![Frame::from_mono(1.); 50]);
+        // the clock never ticks
+        let clock = audio_output.add_clock(60.).unwrap();
+        let stopping = InstanceHandle::new();
+        let scheduled = InstanceHandle::new();
+        let new_instance = InstanceHandle::new();
+
+        audio_output.play(&channel, &play_settings(), &source, None, stopping.clone());
+        audio_output.stop_instance(
+            &channel,
+            &stopping,
+            &AudioTween::default().with_start_time(clock.time() + 1),
+        );
+        let scheduled_settings = PlayAudioSettings {
+            start_time: Some(clock.time() + 1),
+            ..play_settings()
+        };
+        audio_output.play(
+            &channel,
+            &scheduled_settings,
+            &source,
+            None,
+            scheduled.clone(),
+        );
+        let mut frames = 0;
+        while let AudioCommandResult::Retry = audio_output.play(
+            &channel,
+            &play_settings(),
+            &source,
+            None,
+            new_instance.clone(),
+        ) {
+            frames += 1;
+            assert!(frames < 3, "the play is retried every frame");
+            audio_output.tick(Duration::from_millis(10));
+            audio_output.cleanup_stopped_instances();
+        }
+
+        assert_eq!(
+            audio_output.discarded[&channel],
+            vec![(new_instance, PlaybackState::Failed)]
+        );
+        assert!(audio_output.instances[&channel]
+            .iter()
+            .any(|instance| instance.handle == scheduled && instance.is_active()));
+    }
+
+    #[test]
+    fn oldest_sound_is_stolen_when_sound_limit_is_reached() {
+        let mut audio_output = mock_output(AudioSettings {
+            sound_capacity: 1,
+            sound_limit_policy: SoundLimitPolicy::StealOldest,
+            ..default()
+        });
+        let channel = TypeId::of::<crate::MainTrack>();
+        let source = source(vec![Frame::from_mono(1.); 50]);
+        let new_instance = InstanceHandle::new();
+
+        audio_output.play(
+            &channel,
+            &play_settings(),
+            &source,
+            None,
+            InstanceHandle::new(),
+        );
+        audio_output.tick(Duration::from_millis(10));
+        let result = audio_output.play(
+            &channel,
+            &play_settings(),
+            &source,
+            None,
+            new_instance.clone(),
+        );
+        assert!(matches!(result, AudioCommandResult::Retry));
+
+        audio_output.tick(Duration::from_millis(100));
+        audio_output.cleanup_stopped_instances();
+        let result = audio_output.play(
+            &channel,
+            &play_settings(),
+            &source,
+            None,
+            new_instance.clone(),
+        );
+
+        assert!(matches!(result, AudioCommandResult::Ok));
+        assert_eq!(audio_output.instances[&channel].len(), 1);
+        assert_eq!(audio_output.instances[&channel][0].handle, new_instance);
+    }
+
+    #[test]
+    fn effect_parameters_reach_the_effect_at_the_index() {
+        use kira::track::effect::delay::DelayBuilder;
+        use kira::track::effect::filter::{FilterBuilder, FilterMode};
+        struct Effects;
+        let mut audio_output = mock_output(default());
+        audio_output.add_channel::<Effects>(vec![
+            AudioEffect::Delay(DelayBuilder::new().mix(0.)),
+            AudioEffect::Filter(FilterBuilder::new().mode(FilterMode::HighPass).cutoff(5.)),
+        ]);
+        let channel = TypeId::of::<Effects>();
+        let source = source(vec![Frame::from_mono(0.8); 10]);
+        let mut play_settings = play_settings();
+        play_settings.looped = true;
+        audio_output.play(
+            &channel,
+            &play_settings,
+            &source,
+            None,
+            InstanceHandle::new(),
+        );
+        let last_frame = |audio_output: &mut AudioOutput| {
+            let recording = audio_output.render(Duration::from_secs(1)).unwrap();
+            recording.frames.last().unwrap().left
+        };
+
+        // the high pass filter removes the constant signal
+        assert!(last_frame(&mut audio_output).abs() < 0.05);
+
+        let tween = AudioTween::default();
+        let result = audio_output.set_effect_parameter(
+            &channel,
+            0,
+            &EffectParameter::FilterCutoff(1.),
+            &tween,
+        );
+        assert!(matches!(result, AudioCommandResult::Ok));
+        audio_output.set_effect_parameter(&channel, 2, &EffectParameter::Mix(0.), &tween);
+        audio_output.set_effect_parameter(&channel, 0, &EffectParameter::Mix(0.), &tween);
+        assert!(last_frame(&mut audio_output).abs() < 0.05);
+
+        audio_output.set_effect_parameter(&channel, 1, &EffectParameter::Mix(0.), &tween);
+        assert!((last_frame(&mut audio_output) - 0.8).abs() < 1e-4);
+    }
+
+    #[test]
+    fn channel_instance_limit_respects_priority() {
+        let mut audio_output = mock_output(default());
+        let channel = TypeId::of::<crate::MainTrack>();
+        audio_output
+            .channels
+            .get_mut(&channel)
+            .unwrap()
+            .instance_limit = Some(InstanceLimit {
+            max_instances: 1,
+            policy: SoundLimitPolicy::StealOldest,
+        });
+        let source = source(vec![Frame::from_mono(1.); 50]);
+        let mut important = play_settings();
+        important.priority = 1;
+        let low_priority_instance = InstanceHandle::new();
+        let important_instance = InstanceHandle::new();
+
+        audio_output.play(&channel, &important, &source, None, InstanceHandle::new());
+        audio_output.play(
+            &channel,
+            &play_settings(),
+            &source,
+            None,
+            low_priority_instance.clone(),
+        );
+        audio_output.play(
+            &channel,
+            &important,
+            &source,
+            None,
+            important_instance.clone(),
+        );
+        audio_output.tick(Duration::from_millis(100));
+        audio_output.cleanup_stopped_instances();
+
+        assert_eq!(
+            audio_output.discarded[&channel],
+            vec![(low_priority_instance, PlaybackState::Failed)]
+        );
+        assert_eq!(audio_output.instances[&channel].len(), 1);
+        assert_eq!(
+            audio_output.instances[&channel][0].handle,
+            important_instance
+        );
+    }
+
+    #[test]
+    fn instance_limit_holds_for_plays_in_the_same_frame() {
+        let mut app = test_app();
+        let mut audio_output = mock_output(default());
+        let mut channel = AudioChannel::<crate::MainTrack>::default();
+        channel.set_instance_limit(Some(InstanceLimit {
+            max_instances: 2,
+            policy: SoundLimitPolicy::StealOldest,
+        }));
+        let source = app
+            .world
+            .resource_mut::<Assets<AudioSource>>()
+            .add(source(vec![Frame::from_mono(1.); 50]));
+
+        let instances: Vec<InstanceHandle> = (0..4)
+            .map(|_| channel.play(source.clone()).handle())
+            .collect();
+        audio_output.play_channel(
+            app.world.resource::<Assets<AudioSource>>(),
+            &channel,
+            Duration::ZERO,
+        );
+        audio_output.tick(Duration::from_millis(100));
+        audio_output.cleanup_stopped_instances();
+
+        let playing: Vec<InstanceHandle> = audio_output.instances
+            [&TypeId::of::<crate::MainTrack>()]
+            .iter()
+            .map(|instance| instance.handle.clone())
+            .collect();
+        assert_eq!(playing, instances[2..]);
+    }
+
+    #[test]
+    fn cooldown_limits_plays_of_a_source_within_the_window() {
+        let mut audio_output = mock_output(default());
+        let channel = TypeId::of::<crate::MainTrack>();
+        let source = HandleId::random::<AudioSource>();
+        let cooldown = PlayCooldown {
+            max_plays: 2,
+            window: Duration::from_millis(100),
+        };
+
+        assert!(audio_output.cooldown_allows(&channel, source, cooldown));
+        audio_output.record_play(&channel, source);
+        assert!(audio_output.cooldown_allows(&channel, source, cooldown));
+        audio_output.record_play(&channel, source);
+        assert!(!audio_output.cooldown_allows(&channel, source, cooldown));
+        assert!(audio_output.cooldown_allows(
+            &channel,
+            HandleId::random::<AudioSource>(),
+            cooldown
+        ));
+
+        audio_output.channels.get_mut(&channel).unwrap().elapsed += Duration::from_millis(100);
+        assert!(audio_output.cooldown_allows(&channel, source, cooldown));
+    }
+
+    #[test]
+    fn failed_plays_do_not_count_for_the_cooldown() {
+        let mut app = test_app();
+        let mut audio_output = mock_output(default());
+        let mut channel = AudioChannel::<crate::MainTrack>::default();
+        channel.set_cooldown(Some(PlayCooldown {
+            max_plays: 1,
+            window: Duration::from_secs(1),
+        }));
+        channel.set_instance_limit(Some(InstanceLimit {
+            max_instances: 1,
+            policy: SoundLimitPolicy::DropNew,
+        }));
+        let mut audio_sources = app.world.resource_mut::<Assets<AudioSource>>();
+        let other_source = audio_sources.add(source(vec![Frame::from_mono(1.); 50]));
+        let source = audio_sources.add(source(vec![Frame::from_mono(1.); 50]));
+        let channel_id = TypeId::of::<crate::MainTrack>();
+        let play = |audio_output: &mut AudioOutput,
+                    channel: &AudioChannel<crate::MainTrack>,
+                    source: &Handle<AudioSource>| {
+            let instance_handle = channel.play(source.clone()).handle();
+            audio_output.play_channel(
+                app.world.resource::<Assets<AudioSource>>(),
+                channel,
+                Duration::from_millis(10),
+            );
+            instance_handle
+        };
+
+        play(&mut audio_output, &channel, &other_source);
+        let failed = play(&mut audio_output, &channel, &source);
+        assert_eq!(
+            audio_output.discarded[&channel_id][0],
+            (failed, PlaybackState::Failed)
+        );
+
+        channel.set_instance_limit(None);
+        let played = play(&mut audio_output, &channel, &source);
+        assert_eq!(audio_output.instances[&channel_id][1].handle, played);
+
+        let suppressed = play(&mut audio_output, &channel, &source);
+        assert_eq!(
+            audio_output.discarded[&channel_id][0],
+            (suppressed, PlaybackState::Suppressed)
+        );
+        assert_eq!(audio_output.instances[&channel_id].len(), 2);
+    }
+
+    #[test]
+    fn picked_variants_of_removed_groups_are_forgotten() {
+        let mut app = test_app();
+        app.insert_non_send_resource(mock_output(default()))
+            .add_system(cleanup_group_playbacks);
+        let group = app
+            .world
+            .resource_mut::<Assets<AudioSource>>()
+            .add(source(vec![Frame::from_mono(1.); 10]));
+        app.world
+            .non_send_resource_mut::<AudioOutput>()
+            .group_playbacks
+            .insert(group.id, GroupPlayback::default());
+        app.update();
+        assert_eq!(
+            app.world
+                .non_send_resource::<AudioOutput>()
+                .group_playbacks
+                .len(),
+            1
+        );
+
+        app.world
+            .resource_mut::<Assets<AudioSource>>()
+            .remove(&group);
+        // removal events are sent at the end of the frame
+        app.update();
+        app.update();
+
+        assert!(app
+            .world
+            .non_send_resource::<AudioOutput>()
+            .group_playbacks
+            .is_empty());
+    }
+
+    #[test]
+    fn remaining_time_includes_playback_rate_reverse_and_loop_end() {
+        let mut audio_output = mock_output(default());
+        let channel = TypeId::of::<crate::MainTrack>();
+        let source = source(vec![Frame::from_mono(1.); 100]);
+        let mut looped = play_settings();
+        looped.looped = true;
+        looped.loop_end = Some(0.5);
+        let mut reversed = play_settings();
+        reversed.reverse = Some(true);
+
+        audio_output.set_playback_rate(&channel, 2., &AudioTween::default());
+        audio_output.play(&channel, &looped, &source, None, InstanceHandle::new());
+        audio_output.play(&channel, &reversed, &source, None, InstanceHandle::new());
+        audio_output.tick(Duration::from_millis(100));
+
+        let remaining = |instance: usize| {
+            audio_output.instances[&channel][instance]
+                .remaining(2.)
+                .unwrap()
+                .as_secs_f64()
+        };
+        assert!((remaining(0) - 0.15).abs() < 0.03, "{}", remaining(0));
+        assert!((remaining(1) - 0.4).abs() < 0.03, "{}", remaining(1));
+    }
+
+    #[test]
+    fn crossfade_fades_out_other_instances_with_equal_power() {
+        let mut audio_output = mock_output(default());
+        let channel = TypeId::of::<crate::MainTrack>();
+        let source = source(vec![Frame::from_mono(1.); 100]);
+        let outgoing = InstanceHandle::new();
+        let incoming = InstanceHandle::new();
+        let mut crossfade_settings = play_settings();
+        crossfade_settings.crossfade = Some(Duration::from_millis(400));
+
+        audio_output.play(&channel, &play_settings(), &source, None, outgoing.clone());
+        audio_output.play(
+            &channel,
+            &crossfade_settings,
+            &source,
+            None,
+            incoming.clone(),
+        );
+        audio_output.update_crossfades(&channel, Duration::from_millis(200));
+        audio_output.tick(Duration::from_millis(50));
+
+        let instances = &audio_output.instances[&channel];
+        let half_power = std::f64::consts::FRAC_1_SQRT_2;
+        assert!((instances[0].fade - half_power).abs() < 1e-6);
+        assert!((instances[1].fade - half_power).abs() < 1e-6);
+        assert!(matches!(
+            PlaybackState::from(&instances[0]),
+            PlaybackState::Stopping { .. }
+        ));
+        assert!(matches!(
+            PlaybackState::from(&instances[1]),
+            PlaybackState::Playing { .. }
+        ));
+
+        audio_output.update_crossfades(&channel, Duration::from_millis(200));
+        audio_output.tick(Duration::from_millis(50));
+        audio_output.cleanup_stopped_instances();
+
+        assert_eq!(audio_output.instances[&channel].len(), 1);
+        assert_eq!(audio_output.instances[&channel][0].handle, incoming);
+        assert_eq!(audio_output.instances[&channel][0].fade, 1.);
+        assert!(audio_output.channels[&channel].crossfades.is_empty());
+    }
+
+    #[test]
+    fn instances_seek_to_and_by_positions() {
+        let mut audio_output = mock_output(default());
+        let channel = TypeId::of::<crate::MainTrack>();
+        let source = source(vec![Frame::from_mono(1.); 100]);
+        let instance_handle = InstanceHandle::new();
+        let position =
+            |audio_output: &AudioOutput| audio_output.instances[&channel][0].kira.position();
+
+        audio_output.play(
+            &channel,
+            &play_settings(),
+            &source,
+            None,
+            instance_handle.clone(),
+        );
+        audio_output.tick(Duration::from_millis(100));
+        audio_output.seek_to(&channel, &instance_handle, 0.5);
+        audio_output.tick(Duration::from_millis(100));
+        assert!((position(&audio_output) - 0.6).abs() < 0.05);
+
+        audio_output.seek_by(&channel, &instance_handle, -0.3);
+        audio_output.tick(Duration::from_millis(100));
+        assert!((position(&audio_output) - 0.4).abs() < 0.05);
+    }
+
+    #[test]
+    fn loop_regions_loop_until_looping_is_disabled() {
+        let mut audio_output = mock_output(default());
+        let channel = TypeId::of::<crate::MainTrack>();
+        let source = source(vec![Frame::from_mono(1.); 100]);
+        let instance_handle = InstanceHandle::new();
+        let mut play_settings = play_settings();
+        play_settings.looped = true;
+        play_settings.loop_start = Some(0.2);
+        play_settings.loop_end = Some(0.5);
+        let position =
+            |audio_output: &AudioOutput| audio_output.instances[&channel][0].kira.position();
+
+        audio_output.play(
+            &channel,
+            &play_settings,
+            &source,
+            None,
+            instance_handle.clone(),
+        );
+        audio_output.tick(Duration::from_millis(700));
+        assert!((position(&audio_output) - 0.4).abs() < 0.05);
+
+        audio_output.disable_looping(&channel, &instance_handle);
+        audio_output.tick(Duration::from_millis(300));
+        assert!((position(&audio_output) - 0.7).abs() < 0.05);
+        assert!(!audio_output.instances[&channel][0].loops);
+
+        audio_output.tick(Duration::from_millis(400));
+        audio_output.cleanup_stopped_instances();
+        assert!(audio_output.instances[&channel].is_empty());
+    }
+
+    #[test]
+    fn plays_start_at_clock_time() {
+        let mut audio_output = mock_output(default());
+        let channel = TypeId::of::<crate::MainTrack>();
+        let source = source(vec![Frame::from_mono(1.); 100]);
+        let clock = audio_output.add_clock(600.).unwrap();
+        let mut play_settings = play_settings();
+        play_settings.start_time = Some(clock.time() + 3);
+        let position =
+            |audio_output: &AudioOutput| audio_output.instances[&channel][0].kira.position();
+
+        clock.start();
+        audio_output.play(
+            &channel,
+            &play_settings,
+            &source,
+            None,
+            InstanceHandle::new(),
+        );
+        audio_output.tick(Duration::from_millis(200));
+        assert!(clock.ticking());
+        assert_eq!(position(&audio_output), 0.);
+
+        audio_output.tick(Duration::from_millis(300));
+        assert!((position(&audio_output) - 0.2).abs() < 0.05);
+    }
+
+    #[test]
+    fn beats_and_bars_of_the_source_tempo_are_sent() {
+        let mut audio_output = mock_output(default());
+        let channel = TypeId::of::<crate::MainTrack>();
+        let mut source = source(vec![Frame::from_mono(1.); 100]);
+        source.tempo = Some(Tempo::new(600.).with_beats_per_bar(2));
+        let instance_handle = InstanceHandle::new();
+        audio_output.play(
+            &channel,
+            &play_settings(),
+            &source,
+            None,
+            instance_handle.clone(),
+        );
+        let mut app = App::new();
+        app.add_event::<AudioBeat>()
+            .add_event::<AudioBar>()
+            .insert_resource(AudioChannel::<crate::MainTrack>::default())
+            .insert_non_send_resource(audio_output)
+            .add_system(update_instance_states::<crate::MainTrack>);
+
+        app.update();
+        app.world
+            .non_send_resource_mut::<AudioOutput>()
+            .tick(Duration::from_millis(250));
+        app.update();
+
+        let beats: Vec<(u64, u64, u32)> = app
+            .world
+            .resource_mut::<Events<AudioBeat>>()
+            .drain()
+            .map(|beat| (beat.beat, beat.bar, beat.beat_in_bar))
+            .collect();
+        assert_eq!(beats, vec![(0, 0, 0), (1, 0, 1), (2, 1, 0)]);
+        let bars: Vec<u64> = app
+            .world
+            .resource_mut::<Events<AudioBar>>()
+            .drain()
+            .map(|bar| bar.bar)
+            .collect();
+        assert_eq!(bars, vec![0, 1]);
+    }
+
+    #[test]
+    fn quantized_plays_wait_for_the_next_bar_of_the_reference() {
+        let mut audio_output = mock_output(default());
+        let channel = TypeId::of::<crate::MainTrack>();
+        let mut source = source(vec![Frame::from_mono(1.); 100]);
+        source.tempo = Some(Tempo::new(600.));
+        let reference = InstanceHandle::new();
+        let mut next_bar =
+            QuantizedStart::new(Quantize::NextBar, QuantizeReference::new(reference.clone()));
+        let mut unknown_reference = QuantizedStart::new(
+            Quantize::NextBar,
+            QuantizeReference::new(InstanceHandle::new()),
+        );
+
+        audio_output.play(&channel, &play_settings(), &source, None, reference);
+        audio_output.tick(Duration::from_millis(150));
+        let time_until_start = audio_output.time_until_quantized_start(&mut next_bar);
+        assert!((time_until_start - 0.25).abs() < 0.05);
+        assert_eq!(
+            audio_output.time_until_quantized_start(&mut unknown_reference),
+            0.
+        );
+
+        audio_output.tick(Duration::from_millis(250));
+        assert!(audio_output.time_until_quantized_start(&mut next_bar) < 0.05);
+    }
+
+    #[test]
+    fn quantized_plays_are_scheduled_on_the_clock() {
+        let mut app = test_app();
+        let mut audio_output = mock_output(default());
+        let mut channel = AudioChannel::<crate::MainTrack>::default();
+        let channel_id = TypeId::of::<crate::MainTrack>();
+        let mut music = source(vec![Frame::from_mono(1.); 100]);
+        music.tempo = Some(Tempo::new(600.));
+        let stinger = app
+            .world
+            .resource_mut::<Assets<AudioSource>>()
+            .add(source(vec![Frame::from_mono(1.); 100]));
+        let reference = InstanceHandle::new();
+        audio_output.play(
+            &channel_id,
+            &play_settings(),
+            &music,
+            None,
+            reference.clone(),
+        );
+        audio_output.tick(Duration::from_millis(150));
+        channel.set_quantize_reference(Some(QuantizeReference::new(reference.clone())));
+        let stinger = channel.play_quantized(stinger, Quantize::NextBar).handle();
+        let audio_sources = app.world.resource::<Assets<AudioSource>>();
+
+        // the clock for quantized plays starts with the next tick
+        audio_output.play_channel(audio_sources, &channel, Duration::from_millis(10));
+        assert_eq!(channel.commands.read().len(), 1);
+        audio_output.tick(Duration::from_millis(10));
+        let now = audio_output.quantize_clock.as_ref().unwrap().time();
+        let mut next_bar =
+            QuantizedStart::new(Quantize::NextBar, QuantizeReference::new(reference.clone()));
+        // the next bar starts at 0.4 seconds, 240ms after the position of the reference
+        assert_eq!(
+            audio_output.quantized_start_time(&mut next_bar),
+            Some(now + 240)
+        );
+
+        audio_output.play_channel(audio_sources, &channel, Duration::from_millis(10));
+        assert!(channel.commands.read().is_empty());
+        let position = |audio_output: &AudioOutput, instance_handle: &InstanceHandle| {
+            audio_output.instances[&channel_id]
+                .iter()
+                .find(|instance| &instance.handle == instance_handle)
+                .unwrap()
+                .kira
+                .position()
+        };
+        assert_eq!(position(&audio_output, &stinger), 0.);
+        audio_output.tick(Duration::from_millis(330));
+        let reference_position = position(&audio_output, &reference);
+        assert!((position(&audio_output, &stinger) - (reference_position - 0.4)).abs() < 0.015);
+    }
+
+    #[test]
+    fn layers_start_on_the_same_sample_once_all_are_loaded() {
+        let mut app = test_app();
+        let mut audio_output = mock_output(default());
+        let channel = AudioChannel::<crate::MainTrack>::default();
+        let drums = app
+            .world
+            .resource_mut::<Assets<AudioSource>>()
+            .add(source(vec![Frame::from_mono(1.); 100]));
+        let strings: Handle<AudioSource> = Handle::weak(HandleId::random::<AudioSource>());
+        let music = LayeredMusic::new(vec![
+            MusicLayer::new(drums),
+            MusicLayer::new(strings.clone()).with_intensity(0.5),
+        ]);
+        let position = |audio_output: &AudioOutput, layer: usize| {
+            audio_output.instances[&TypeId::of::<crate::MainTrack>()][layer]
+                .kira
+                .position()
+        };
+
+        let layered = channel.play_layered(&music);
+        audio_output.play_channel(
+            app.world.resource::<Assets<AudioSource>>(),
+            &channel,
+            Duration::ZERO,
+        );
+        assert_eq!(
+            channel.state(layered.layers()[0].clone()),
+            PlaybackState::Queued
+        );
+
+        audio_output.tick(Duration::from_millis(300));
+        app.world
+            .resource_mut::<Assets<AudioSource>>()
+            .set_untracked(strings, source(vec![Frame::from_mono(1.); 100]));
+        audio_output.play_channel(
+            app.world.resource::<Assets<AudioSource>>(),
+            &channel,
+            Duration::ZERO,
+        );
+        audio_output.tick(Duration::from_millis(200));
+
+        assert!((position(&audio_output, 0) - 0.2).abs() < 0.05);
+        assert_eq!(position(&audio_output, 0), position(&audio_output, 1));
+        assert_eq!(
+            audio_output.instances[&TypeId::of::<crate::MainTrack>()][1].volume,
+            0.
+        );
+        audio_output.cleanup_stopped_instances();
+        assert!(audio_output.start_clocks.is_empty());
+    }
+
+    #[test]
+    fn layers_fail_together_if_one_layer_fails() {
+        let mut app = test_app();
+        let mut audio_output = mock_output(default());
+        let mut channel = AudioChannel::<crate::MainTrack>::default();
+        channel.set_instance_limit(Some(InstanceLimit {
+            max_instances: 1,
+            policy: SoundLimitPolicy::DropNew,
+        }));
+        let mut audio_sources = app.world.resource_mut::<Assets<AudioSource>>();
+        let music = LayeredMusic::new(vec![
+            MusicLayer::new(audio_sources.add(source(vec![Frame::from_mono(1.); 100]))),
+            MusicLayer::new(audio_sources.add(source(vec![Frame::from_mono(1.); 100]))),
+        ]);
+        let channel_id = TypeId::of::<crate::MainTrack>();
+
+        let layered = channel.play_layered(&music);
+        audio_output.play_channel(
+            app.world.resource::<Assets<AudioSource>>(),
+            &channel,
+            Duration::ZERO,
+        );
+
+        assert!(audio_output.instances[&channel_id].is_empty());
+        assert!(audio_output.start_clocks.is_empty());
+        for layer in layered.layers() {
+            assert!(audio_output.discarded[&channel_id]
+                .contains(&(layer.clone(), PlaybackState::Failed)));
         }
+        assert_eq!(audio_output.discarded[&channel_id].len(), 2);
+        let events = audio_output.cleanup_stopped_instances();
+        assert_eq!(events.len(), 2);
+        assert!(events
+            .iter()
+            .all(|event| matches!(event, InstanceEvent::PlayFailed(_))));
     }
 }
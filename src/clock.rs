@@ -0,0 +1,99 @@
+use crate::tween::AudioTween;
+use bevy::log::error;
+use kira::clock::{ClockHandle, ClockTime};
+use kira::ClockSpeed;
+use std::fmt;
+
+/// A clock to schedule audio commands sample accurate
+///
+/// Clocks tick with a tempo in ticks per minute (e.g. once per beat). Create clocks with
+/// [`AudioOutput::add_clock`](crate::AudioOutput::add_clock). Plays can start at a clock tick
+/// with [`PlayAudioCommand::start_at`](crate::PlayAudioCommand::start_at) and all commands taking
+/// an [`AudioTween`] can be scheduled with [`AudioTween::with_start_time`].
+///
+/// The clock is removed when this value is dropped.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{Audio, AudioClock, AudioOutput};
+/// struct Beat(AudioClock);
+///
+/// fn start_beat(mut commands: Commands, mut audio_output: NonSendMut<AudioOutput>) {
+///     if let Some(clock) = audio_output.add_clock(120.) {
+///         clock.start();
+///         commands.insert_resource(Beat(clock));
+///     }
+/// }
+///
+/// fn play_drums_and_bass(audio: Res<Audio>, beat: Res<Beat>, asset_server: Res<AssetServer>) {
+///     // start both on the same sample at the next beat
+///     let next_beat = beat.0.time() + 1;
+///     audio.play(asset_server.load("drums.ogg")).start_at(next_beat);
+///     audio.play(asset_server.load("bass.ogg")).start_at(next_beat);
+/// }
+/// ```
+pub struct AudioClock {
+    handle: ClockHandle,
+}
+
+impl fmt::Debug for AudioClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AudioClock")
+            .field("time", &self.time())
+            .finish()
+    }
+}
+
+impl AudioClock {
+    pub(crate) fn new(handle: ClockHandle) -> Self {
+        AudioClock { handle }
+    }
+
+    /// The current time of the clock
+    ///
+    /// Add ticks to the time to schedule commands in the future.
+    pub fn time(&self) -> ClockTime {
+        self.handle.time()
+    }
+
+    /// The time between the last and the next tick from 0 to 1
+    pub fn fractional_position(&self) -> f64 {
+        self.handle.fractional_position()
+    }
+
+    /// Whether the clock is running
+    pub fn ticking(&self) -> bool {
+        self.handle.ticking()
+    }
+
+    /// Start or resume the clock
+    pub fn start(&self) {
+        if let Err(error) = self.handle.start() {
+            error!("Failed to start clock: {:?}", error);
+        }
+    }
+
+    /// Pause the clock
+    pub fn pause(&self) {
+        if let Err(error) = self.handle.pause() {
+            error!("Failed to pause clock: {:?}", error);
+        }
+    }
+
+    /// Stop the clock and reset its time to 0
+    pub fn stop(&self) {
+        if let Err(error) = self.handle.stop() {
+            error!("Failed to stop clock: {:?}", error);
+        }
+    }
+
+    /// Change the tempo of the clock in ticks per minute
+    pub fn set_tempo(&self, ticks_per_minute: f64, tween: AudioTween) {
+        if let Err(error) = self.handle.set_speed(
+            ClockSpeed::TicksPerMinute(ticks_per_minute),
+            (&tween).into(),
+        ) {
+            error!("Failed to set tempo of clock: {:?}", error);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use kira::track::effect::delay::{DelayBuilder, DelayHandle};
+use kira::track::effect::distortion::{DistortionBuilder, DistortionHandle, DistortionKind};
+use kira::track::effect::filter::{FilterBuilder, FilterHandle, FilterMode};
+use kira::track::effect::reverb::{ReverbBuilder, ReverbHandle};
+use kira::track::TrackBuilder;
+use kira::tween::Tween;
+use kira::CommandError;
+
+/// An effect on the mixer track of an audio channel
+///
+/// Effects are added to a channel with
+/// [`add_audio_channel_with_effects`](crate::AudioApp::add_audio_channel_with_effects) and
+/// process all sounds playing in that channel in the given order. Their parameters can be changed
+/// at run-time using [`AudioChannel::set_effect_parameter`](crate::AudioChannel::set_effect_parameter).
+///
+/// Kira does not offer a compressor effect yet.
+pub enum AudioEffect {
+    /// Reverberation simulating sound reflections of a room
+    Reverb(ReverbBuilder),
+    /// Filter removing frequencies, e.g. a low pass filter to muffle sounds
+    Filter(FilterBuilder),
+    /// Echo repeating the sound after a delay
+    Delay(DelayBuilder),
+    /// Distortion making sounds harsher
+    Distortion(DistortionBuilder),
+}
+
+impl AudioEffect {
+    pub(crate) fn add_to(self, track_builder: &mut TrackBuilder) -> EffectHandle {
+        match self {
+            AudioEffect::Reverb(builder) => EffectHandle::Reverb(track_builder.add_effect(builder)),
+            AudioEffect::Filter(builder) => EffectHandle::Filter(track_builder.add_effect(builder)),
+            AudioEffect::Delay(builder) => EffectHandle::Delay(track_builder.add_effect(builder)),
+            AudioEffect::Distortion(builder) => {
+                EffectHandle::Distortion(track_builder.add_effect(builder))
+            }
+        }
+    }
+}
+
+/// A parameter of an [`AudioEffect`] that can be changed at run-time
+#[derive(Clone, Copy, Debug)]
+pub enum EffectParameter {
+    /// How much of the processed signal is blended with the unprocessed one (0 to 1)
+    ///
+    /// Supported by all effects.
+    Mix(f64),
+    /// The frequencies a [`AudioEffect::Filter`] keeps or removes
+    ///
+    /// This parameter cannot be tweened.
+    FilterMode(FilterMode),
+    /// The cutoff frequency of a [`AudioEffect::Filter`] in Hz
+    FilterCutoff(f64),
+    /// The resonance of a [`AudioEffect::Filter`] (0 to 1)
+    FilterResonance(f64),
+    /// How much the room of a [`AudioEffect::Reverb`] reflects (0 to 1)
+    ReverbFeedback(f64),
+    /// How quickly high frequencies disappear from a [`AudioEffect::Reverb`] (0 to 1)
+    ReverbDamping(f64),
+    /// The stereo width of a [`AudioEffect::Reverb`] (0 is mono, 1 is full stereo)
+    ReverbStereoWidth(f64),
+    /// The delay of a [`AudioEffect::Delay`] in seconds
+    DelayTime(f64),
+    /// The amplitude of the repeated signal of a [`AudioEffect::Delay`]
+    DelayFeedback(f64),
+    /// The kind of a [`AudioEffect::Distortion`]
+    ///
+    /// This parameter cannot be tweened.
+    DistortionKind(DistortionKind),
+    /// How much distortion a [`AudioEffect::Distortion`] applies (amplitude)
+    DistortionDrive(f64),
+}
+
+pub(crate) enum EffectHandle {
+    Reverb(ReverbHandle),
+    Filter(FilterHandle),
+    Delay(DelayHandle),
+    Distortion(DistortionHandle),
+}
+
+pub(crate) enum SetEffectParameterError {
+    UnsupportedParameter,
+    Command(CommandError),
+}
+
+impl From<CommandError> for SetEffectParameterError {
+    fn from(error: CommandError) -> Self {
+        SetEffectParameterError::Command(error)
+    }
+}
+
+impl EffectHandle {
+    pub(crate) fn set(
+        &mut self,
+        parameter: &EffectParameter,
+        tween: Tween,
+    ) -> Result<(), SetEffectParameterError> {
+        match (self, *parameter) {
+            (EffectHandle::Reverb(reverb), EffectParameter::Mix(mix)) => {
+                reverb.set_mix(mix, tween)?
+            }
+            (EffectHandle::Filter(filter), EffectParameter::Mix(mix)) => {
+                filter.set_mix(mix, tween)?
+            }
+            (EffectHandle::Delay(delay), EffectParameter::Mix(mix)) => delay.set_mix(mix, tween)?,
+            (EffectHandle::Distortion(distortion), EffectParameter::Mix(mix)) => {
+                distortion.set_mix(mix, tween)?
+            }
+            (EffectHandle::Filter(filter), EffectParameter::FilterMode(mode)) => {
+                filter.set_mode(mode)?
+            }
+            (EffectHandle::Filter(filter), EffectParameter::FilterCutoff(cutoff)) => {
+                filter.set_cutoff(cutoff, tween)?
+            }
+            (EffectHandle::Filter(filter), EffectParameter::FilterResonance(resonance)) => {
+                filter.set_resonance(resonance, tween)?
+            }
+            (EffectHandle::Reverb(reverb), EffectParameter::ReverbFeedback(feedback)) => {
+                reverb.set_feedback(feedback, tween)?
+            }
+            (EffectHandle::Reverb(reverb), EffectParameter::ReverbDamping(damping)) => {
+                reverb.set_damping(damping, tween)?
+            }
+            (EffectHandle::Reverb(reverb), EffectParameter::ReverbStereoWidth(stereo_width)) => {
+                reverb.set_stereo_width(stereo_width, tween)?
+            }
+            (EffectHandle::Delay(delay), EffectParameter::DelayTime(delay_time)) => {
+                delay.set_delay_time(delay_time, tween)?
+            }
+            (EffectHandle::Delay(delay), EffectParameter::DelayFeedback(feedback)) => {
+                delay.set_feedback(feedback, tween)?
+            }
+            (EffectHandle::Distortion(distortion), EffectParameter::DistortionKind(kind)) => {
+                distortion.set_kind(kind)?
+            }
+            (EffectHandle::Distortion(distortion), EffectParameter::DistortionDrive(drive)) => {
+                distortion.set_drive(drive, tween)?
+            }
+            _ => return Err(SetEffectParameterError::UnsupportedParameter),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use kira::manager::backend::mock::MockBackend;
+    use kira::manager::{AudioManager, AudioManagerSettings};
+
+    #[test]
+    fn effects_reject_parameters_of_other_effects() {
+        let mut manager =
+            AudioManager::<MockBackend>::new(AudioManagerSettings::default()).unwrap();
+        let mut track_builder = TrackBuilder::new();
+        let mut reverb = AudioEffect::Reverb(ReverbBuilder::new()).add_to(&mut track_builder);
+        let mut filter = AudioEffect::Filter(FilterBuilder::new()).add_to(&mut track_builder);
+        let _track = manager.add_sub_track(track_builder).unwrap();
+
+        assert!(matches!(
+            reverb.set(&EffectParameter::FilterCutoff(500.), Tween::default()),
+            Err(SetEffectParameterError::UnsupportedParameter)
+        ));
+        assert!(matches!(
+            filter.set(&EffectParameter::ReverbFeedback(0.5), Tween::default()),
+            Err(SetEffectParameterError::UnsupportedParameter)
+        ));
+        assert!(filter
+            .set(&EffectParameter::FilterCutoff(500.), Tween::default())
+            .is_ok());
+        assert!(reverb
+            .set(&EffectParameter::Mix(0.2), Tween::default())
+            .is_ok());
+        assert!(filter
+            .set(&EffectParameter::Mix(0.2), Tween::default())
+            .is_ok());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::audio::InstanceHandle;
+use bevy::ecs::system::{Resource, SystemParam};
+use bevy::prelude::EventWriter;
+use std::any::TypeId;
+
+/// Event sent when a playback instance started playing
+///
+/// The `channel` is the [`TypeId`] of the channel's type parameter.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::AudioStarted;
+/// struct Music;
+///
+/// fn log_music(mut started: EventReader<AudioStarted>) {
+///     for event in started.iter().filter(|event| event.is_in::<Music>()) {
+///         info!("Started music {:?}", event.handle);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioStarted {
+    /// The channel the instance is playing in
+    pub channel: TypeId,
+    /// The started instance
+    pub handle: InstanceHandle,
+}
+
+/// Event sent when a looping playback instance jumped back to its loop start
+///
+/// The `channel` is the [`TypeId`] of the channel's type parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioLooped {
+    /// The channel the instance is playing in
+    pub channel: TypeId,
+    /// The looped instance
+    pub handle: InstanceHandle,
+}
+
+/// Event sent when a playback instance stopped
+///
+/// Instances finish when they reached their end or when they were stopped.
+/// The `channel` is the [`TypeId`] of the channel's type parameter.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{AudioFinished, InstanceHandle};
+/// struct DialogueLine(InstanceHandle);
+///
+/// fn next_dialogue_line(mut finished: EventReader<AudioFinished>, line: Res<DialogueLine>) {
+///     for event in finished.iter() {
+///         if event.handle == line.0 {
+///             // play the next line
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioFinished {
+    /// The channel the instance was playing in
+    pub channel: TypeId,
+    /// The finished instance
+    pub handle: InstanceHandle,
+}
+
+/// Event sent when a playback instance could not be started
+///
+/// The reason is logged as error. The `channel` is the [`TypeId`] of the channel's type parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioPlayFailed {
+    /// The channel the instance should have played in
+    pub channel: TypeId,
+    /// The failed instance
+    pub handle: InstanceHandle,
+}
+
+/// Event sent when a playback instance reached a beat of the [`Tempo`](crate::Tempo) of its
+/// audio source
+///
+/// The `channel` is the [`TypeId`] of the channel's type parameter. Beats are detected once per
+/// frame, so the event arrives up to a frame after the beat was played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioBeat {
+    /// The channel the instance is playing in
+    pub channel: TypeId,
+    /// The playing instance
+    pub handle: InstanceHandle,
+    /// Number of the beat, starting at 0 for the first beat
+    pub beat: u64,
+    /// Number of the bar the beat is in
+    pub bar: u64,
+    /// Number of the beat in its bar, starting at 0
+    pub beat_in_bar: u32,
+}
+
+/// Event sent when a playback instance reached the first beat of a bar
+///
+/// Sent together with the [`AudioBeat`] of the first beat. The `channel` is the [`TypeId`] of
+/// the channel's type parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioBar {
+    /// The channel the instance is playing in
+    pub channel: TypeId,
+    /// The playing instance
+    pub handle: InstanceHandle,
+    /// Number of the bar, starting at 0 for the first bar
+    pub bar: u64,
+}
+
+macro_rules! impl_is_in {
+    ($($event:ty),*) => {
+        $(
+            impl $event {
+                /// Check if this event belongs to the channel with the given type parameter
+                pub fn is_in<T: Resource>(&self) -> bool {
+                    self.channel == TypeId::of::<T>()
+                }
+            }
+        )*
+    };
+}
+
+impl_is_in!(
+    AudioStarted,
+    AudioLooped,
+    AudioFinished,
+    AudioPlayFailed,
+    AudioBeat,
+    AudioBar
+);
+
+/// Instance lifecycle changes collected by the audio output
+pub(crate) enum InstanceEvent {
+    Started(AudioStarted),
+    Looped(AudioLooped),
+    Finished(AudioFinished),
+    PlayFailed(AudioPlayFailed),
+}
+
+#[derive(SystemParam)]
+pub(crate) struct InstanceEventWriters<'w, 's> {
+    started: EventWriter<'w, 's, AudioStarted>,
+    looped: EventWriter<'w, 's, AudioLooped>,
+    finished: EventWriter<'w, 's, AudioFinished>,
+    play_failed: EventWriter<'w, 's, AudioPlayFailed>,
+}
+
+impl<'w, 's> InstanceEventWriters<'w, 's> {
+    pub(crate) fn send(&mut self, event: InstanceEvent) {
+        match event {
+            InstanceEvent::Started(event) => self.started.send(event),
+            InstanceEvent::Looped(event) => self.looped.send(event),
+            InstanceEvent::Finished(event) => self.finished.send(event),
+            InstanceEvent::PlayFailed(event) => self.play_failed.send(event),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::audio::{InstanceHandle, PlayAudioCommandArgs};
+use crate::play::PlayAudioSettings;
+use crate::source::AudioSource;
+use bevy::asset::Handle;
+
+/// Music made of stems that play sample-synchronized
+///
+/// All layers start on the same sample with
+/// [`AudioChannel::play_layered`](crate::AudioChannel::play_layered). Every layer has an
+/// intensity from which on it is audible, so that layers can be faded in and out by changing the
+/// intensity of the music with [`AudioChannel::set_intensity`](crate::AudioChannel::set_intensity).
+/// Silent layers keep playing to stay in sync.
+///
+/// Looping layers stay synchronized if all stems have the same length.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{Audio, AudioTween, LayeredInstance, LayeredMusic, MusicLayer};
+/// # use std::time::Duration;
+/// struct CombatMusic(LayeredInstance);
+///
+/// fn start_music(mut commands: Commands, asset_server: Res<AssetServer>, audio: Res<Audio>) {
+///     let music = LayeredMusic::new(vec![
+///         MusicLayer::new(asset_server.load("music/drums.ogg")),
+///         MusicLayer::new(asset_server.load("music/strings.ogg")).with_intensity(0.5),
+///         MusicLayer::new(asset_server.load("music/percussion.ogg")).with_intensity(0.8),
+///     ])
+///     .looped();
+///     commands.insert_resource(CombatMusic(audio.play_layered(&music)));
+/// }
+///
+/// fn enemies_approach(music: Res<CombatMusic>, audio: Res<Audio>) {
+///     // fade in the strings
+///     audio.set_intensity(
+///         &music.0,
+///         0.6,
+///         AudioTween::linear(Duration::from_secs(2)),
+///     );
+///     // layers are instances that can be controlled on their own
+///     audio.set_instance_volume(&music.0.layers()[0], 0.5);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayeredMusic {
+    /// The stems of the music
+    pub layers: Vec<MusicLayer>,
+    /// Whether all layers loop
+    pub looped: bool,
+    /// The intensity the music starts with
+    pub intensity: f64,
+}
+
+/// A stem of [`LayeredMusic`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MusicLayer {
+    /// The sound of this layer
+    pub source: Handle<AudioSource>,
+    /// The intensity of the music from which on this layer is audible
+    pub intensity: f64,
+}
+
+/// The playing instances of [`LayeredMusic`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayeredInstance {
+    layers: Vec<InstanceHandle>,
+    intensities: Vec<f64>,
+}
+
+impl LayeredMusic {
+    /// Create music of the given layers that does not loop and starts with intensity 0
+    pub fn new(layers: Vec<MusicLayer>) -> Self {
+        LayeredMusic {
+            layers,
+            looped: false,
+            intensity: 0.,
+        }
+    }
+
+    /// Loop all layers
+    pub fn looped(mut self) -> Self {
+        self.looped = true;
+        self
+    }
+
+    /// Set the intensity the music starts with
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Play commands of all layers
+    pub(crate) fn play_args(&self, instance: &LayeredInstance) -> Vec<PlayAudioCommandArgs> {
+        self.layers
+            .iter()
+            .zip(instance.volumes_at(self.intensity))
+            .map(|(layer, (instance_handle, volume))| {
+                let mut settings = PlayAudioSettings::new(layer.source.clone());
+                settings.looped = self.looped;
+                settings.volume = Some(volume);
+                PlayAudioCommandArgs {
+                    settings,
+                    instance_handle: instance_handle.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl MusicLayer {
+    /// Create a layer that is always audible
+    pub fn new(source: Handle<AudioSource>) -> Self {
+        MusicLayer {
+            source,
+            intensity: 0.,
+        }
+    }
+
+    /// Set the intensity of the music from which on this layer is audible
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+}
+
+impl LayeredInstance {
+    pub(crate) fn new(music: &LayeredMusic) -> Self {
+        LayeredInstance {
+            layers: music.layers.iter().map(|_| InstanceHandle::new()).collect(),
+            intensities: music.layers.iter().map(|layer| layer.intensity).collect(),
+        }
+    }
+
+    /// The instances of the layers in the order of [`LayeredMusic::layers`]
+    pub fn layers(&self) -> &[InstanceHandle] {
+        &self.layers
+    }
+
+    /// The volume of every layer at the given intensity
+    pub(crate) fn volumes_at(
+        &self,
+        intensity: f64,
+    ) -> impl Iterator<Item = (&InstanceHandle, f64)> + '_ {
+        self.layers
+            .iter()
+            .zip(self.intensities.iter())
+            .map(move |(layer, layer_intensity)| {
+                let volume = if intensity >= *layer_intensity {
+                    1.
+                } else {
+                    0.
+                };
+                (layer, volume)
+            })
+    }
+}
@@ -36,28 +36,76 @@
 
 mod audio;
 mod audio_output;
+mod clock;
+mod effects;
+mod events;
+mod layers;
+mod manager;
+mod play;
+mod playlist;
+mod quantize;
+mod recording;
+mod sequence;
 mod settings;
 mod source;
+mod spatial;
+mod tempo;
+mod tween;
 
 pub use audio::{AudioApp, AudioChannel, InstanceHandle, PlaybackState};
-pub use settings::AudioSettings;
-pub use source::AudioSource;
+pub use audio_output::AudioOutput;
+pub use clock::AudioClock;
+pub use effects::{AudioEffect, EffectParameter};
+pub use events::{AudioBar, AudioBeat, AudioFinished, AudioLooped, AudioPlayFailed, AudioStarted};
+pub use kira::clock::ClockTime;
+pub use kira::dsp::Frame;
+pub use kira::track::effect::{
+    delay::DelayBuilder,
+    distortion::{DistortionBuilder, DistortionKind},
+    filter::{FilterBuilder, FilterMode},
+    reverb::ReverbBuilder,
+};
+pub use layers::{LayeredInstance, LayeredMusic, MusicLayer};
+pub use play::PlayAudioCommand;
+pub use playlist::{Playlist, PlaylistMode};
+pub use quantize::{Quantize, QuantizeReference};
+pub use recording::{AudioRecording, SampleRateMismatch};
+pub use sequence::{
+    MusicSegment, MusicSequence, MusicSequencer, MusicTransition, TransitionTiming,
+};
+pub use settings::{AudioBackend, AudioSettings, InstanceLimit, PlayCooldown, SoundLimitPolicy};
+pub use source::group::{AudioSourceGroup, AudioSourceVariant, GroupMode};
+pub use source::{AudioSource, AudioSourceData};
+pub use spatial::{Attenuation, AudioEmitter, AudioReceiver, DistanceModel, SpatialAudio};
+pub use tempo::Tempo;
+pub use tween::{AudioEasing, AudioTween, TweenCommand};
 
-use crate::audio_output::{cleanup_stopped_instances, AudioOutput};
+use crate::audio_output::{cleanup_group_playbacks, cleanup_stopped_instances};
+use crate::spatial::run_spatial_audio;
 
 #[cfg(feature = "flac")]
 use crate::source::flac_loader::FlacLoader;
+#[cfg(feature = "settings_loader")]
+use crate::source::group_loader::GroupLoader;
 #[cfg(feature = "mp3")]
 use crate::source::mp3_loader::Mp3Loader;
 #[cfg(feature = "ogg")]
 use crate::source::ogg_loader::OggLoader;
 #[cfg(feature = "settings_loader")]
+use crate::source::sequence_loader::SequenceLoader;
+#[cfg(feature = "settings_loader")]
 use crate::source::settings_loader::SettingsLoader;
+#[cfg(all(
+    any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+    not(target_arch = "wasm32")
+))]
+use crate::source::streaming_loader::StreamingLoader;
 #[cfg(feature = "wav")]
 use crate::source::wav_loader::WavLoader;
 use bevy::prelude::{
     AddAsset, App, CoreStage, ParallelSystemDescriptorCoercion, Plugin, SystemLabel,
 };
+use bevy::transform::TransformSystem;
 
 /// A Bevy plugin for audio
 ///
@@ -93,7 +141,15 @@ pub struct AudioPlugin;
 impl Plugin for AudioPlugin {
     fn build(&self, app: &mut App) {
         app.init_non_send_resource::<AudioOutput>()
-            .add_asset::<AudioSource>();
+            .init_resource::<SpatialAudio>()
+            .add_event::<AudioStarted>()
+            .add_event::<AudioLooped>()
+            .add_event::<AudioFinished>()
+            .add_event::<AudioPlayFailed>()
+            .add_event::<AudioBeat>()
+            .add_event::<AudioBar>()
+            .add_asset::<AudioSource>()
+            .add_asset::<MusicSequence>();
 
         #[cfg(feature = "mp3")]
         app.init_asset_loader::<Mp3Loader>();
@@ -103,14 +159,30 @@ impl Plugin for AudioPlugin {
         app.init_asset_loader::<WavLoader>();
         #[cfg(feature = "flac")]
         app.init_asset_loader::<FlacLoader>();
+        #[cfg(all(
+            any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+            not(target_arch = "wasm32")
+        ))]
+        app.init_asset_loader::<StreamingLoader>();
 
         #[cfg(feature = "settings_loader")]
         app.init_asset_loader::<SettingsLoader>();
+        #[cfg(feature = "settings_loader")]
+        app.init_asset_loader::<GroupLoader>();
+        #[cfg(feature = "settings_loader")]
+        app.init_asset_loader::<SequenceLoader>();
 
         app.add_system_to_stage(
             CoreStage::PreUpdate,
             cleanup_stopped_instances.label(AudioSystemLabel::InstanceCleanup),
         )
+        .add_system_to_stage(CoreStage::PreUpdate, cleanup_group_playbacks)
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            run_spatial_audio
+                .after(TransformSystem::TransformPropagate)
+                .after(AudioSystemLabel::PlayAudio),
+        )
         .add_audio_channel::<MainTrack>();
     }
 }
@@ -118,6 +190,8 @@ impl Plugin for AudioPlugin {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
 pub(crate) enum AudioSystemLabel {
     InstanceCleanup,
+    InstanceStates,
+    PlayAudio,
 }
 
 /// The default audio channel
@@ -0,0 +1,136 @@
+use crate::settings::{AudioBackend, AudioSettings};
+use bevy::log::warn;
+use kira::clock::ClockHandle;
+use kira::dsp::Frame;
+use kira::manager::backend::mock::{MockBackend, MockBackendSettings};
+#[cfg(feature = "cpal")]
+use kira::manager::backend::DefaultBackend;
+use kira::manager::error::{AddClockError, AddSubTrackError, PlaySoundError};
+use kira::manager::AudioManager;
+use kira::sound::SoundData;
+use kira::track::{TrackBuilder, TrackHandle};
+use kira::ClockSpeed;
+use std::time::Duration;
+
+/// Duration the mock backend processes between updating the state of sounds
+///
+/// Audio devices usually process buffers of around 10ms.
+const MOCK_BUFFERS_PER_SECOND: u32 = 100;
+
+/// Kira audio manager with the configured backend
+pub(crate) enum Manager {
+    #[cfg(feature = "cpal")]
+    Default(AudioManager<DefaultBackend>),
+    Mock {
+        manager: Box<AudioManager<MockBackend>>,
+        sample_rate: u32,
+        /// Fraction of a frame left over from the last tick
+        remainder: f64,
+    },
+}
+
+impl Manager {
+    pub(crate) fn new(settings: &AudioSettings) -> Option<Self> {
+        match settings.backend {
+            #[cfg(feature = "cpal")]
+            AudioBackend::Default => match AudioManager::new(settings.manager_settings(())) {
+                Ok(manager) => Some(Manager::Default(manager)),
+                Err(setup_error) => {
+                    warn!("Failed to setup audio: {:?}", setup_error);
+                    None
+                }
+            },
+            #[cfg(not(feature = "cpal"))]
+            AudioBackend::Default => {
+                warn!("Playing audio on an output device requires the feature `cpal`");
+                None
+            }
+            AudioBackend::Mock { sample_rate } => {
+                match AudioManager::new(
+                    settings.manager_settings(MockBackendSettings { sample_rate }),
+                ) {
+                    Ok(manager) => Some(Manager::Mock {
+                        manager: Box::new(manager),
+                        sample_rate,
+                        remainder: 0.,
+                    }),
+                    Err(()) => {
+                        warn!("Failed to setup mock audio backend");
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn play<D: SoundData>(
+        &mut self,
+        sound_data: D,
+    ) -> Result<D::Handle, PlaySoundError<D::Error>> {
+        match self {
+            #[cfg(feature = "cpal")]
+            Manager::Default(manager) => manager.play(sound_data),
+            Manager::Mock { manager, .. } => manager.play(sound_data),
+        }
+    }
+
+    pub(crate) fn add_sub_track(
+        &mut self,
+        builder: TrackBuilder,
+    ) -> Result<TrackHandle, AddSubTrackError> {
+        match self {
+            #[cfg(feature = "cpal")]
+            Manager::Default(manager) => manager.add_sub_track(builder),
+            Manager::Mock { manager, .. } => manager.add_sub_track(builder),
+        }
+    }
+
+    pub(crate) fn add_clock(&mut self, speed: ClockSpeed) -> Result<ClockHandle, AddClockError> {
+        match self {
+            #[cfg(feature = "cpal")]
+            Manager::Default(manager) => manager.add_clock(speed),
+            Manager::Mock { manager, .. } => manager.add_clock(speed),
+        }
+    }
+
+    /// The sample rate of the mock backend
+    pub(crate) fn mock_sample_rate(&self) -> Option<u32> {
+        match self {
+            #[cfg(feature = "cpal")]
+            Manager::Default(_) => None,
+            Manager::Mock { sample_rate, .. } => Some(*sample_rate),
+        }
+    }
+
+    /// Process the given duration of audio with the mock backend
+    ///
+    /// Every processed frame of the main output is passed to `on_frame`.
+    /// Does nothing if the manager does not use the mock backend.
+    pub(crate) fn tick(&mut self, duration: Duration, mut on_frame: impl FnMut(Frame)) {
+        match self {
+            #[cfg(feature = "cpal")]
+            Manager::Default(_) => (),
+            Manager::Mock {
+                manager,
+                sample_rate,
+                remainder,
+            } => {
+                let frames = duration.as_secs_f64() * *sample_rate as f64 + *remainder;
+                *remainder = frames.fract();
+                let backend = manager.backend_mut();
+                // Commands are run and the state of sounds is published when processing starts
+                backend.on_start_processing();
+                let buffer_frames = (*sample_rate / MOCK_BUFFERS_PER_SECOND).max(1) as u64;
+                let mut frames = frames as u64;
+                while frames > 0 {
+                    let buffer = frames.min(buffer_frames);
+                    for _ in 0..buffer {
+                        on_frame(backend.process());
+                    }
+                    backend.on_start_processing();
+                    frames -= buffer;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use crate::audio::{AudioCommand, InstanceHandle, PlayAudioCommandArgs};
+use crate::quantize::QuantizedStart;
+use crate::source::AudioSource;
+use crate::tween::AudioTween;
+use bevy::asset::Handle;
+use kira::clock::ClockTime;
+use kira::sound::static_sound::StaticSoundSettings;
+use kira::{LoopBehavior, PlaybackRate, StartTime, Volume};
+use parking_lot::RwLock;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PlayAudioSettings {
+    pub source: Handle<AudioSource>,
+    pub intro_source: Option<Handle<AudioSource>>,
+    pub looped: bool,
+    pub loop_start: Option<f64>,
+    pub loop_end: Option<f64>,
+    pub volume: Option<f64>,
+    pub panning: Option<f64>,
+    pub playback_rate: Option<f64>,
+    pub start_position: Option<f64>,
+    pub reverse: Option<bool>,
+    pub fade_in: Option<AudioTween>,
+    pub start_delay: Duration,
+    pub start_time: Option<ClockTime>,
+    pub priority: i32,
+    pub crossfade: Option<Duration>,
+    pub quantize: Option<QuantizedStart>,
+}
+
+impl PlayAudioSettings {
+    pub(crate) fn new(source: Handle<AudioSource>) -> Self {
+        PlayAudioSettings {
+            source,
+            intro_source: None,
+            looped: false,
+            loop_start: None,
+            loop_end: None,
+            volume: None,
+            panning: None,
+            playback_rate: None,
+            start_position: None,
+            reverse: None,
+            fade_in: None,
+            start_delay: Duration::ZERO,
+            start_time: None,
+            priority: 0,
+            crossfade: None,
+            quantize: None,
+        }
+    }
+
+    /// Apply the settings of this playback on top of the settings of the sound
+    ///
+    /// Volume and playback rate are multiplied with the current values.
+    /// All other configured values replace the current ones.
+    pub(crate) fn apply(&self, settings: &mut StaticSoundSettings) {
+        if let Some(volume) = self.volume {
+            settings.volume = Volume::Amplitude(settings.volume.as_amplitude() * volume);
+        }
+        if let Some(playback_rate) = self.playback_rate {
+            settings.playback_rate =
+                PlaybackRate::Factor(settings.playback_rate.as_factor() * playback_rate);
+        }
+        if let Some(panning) = self.panning {
+            settings.panning = panning;
+        }
+        if let Some(start_position) = self.start_position {
+            settings.start_position = start_position;
+        }
+        if let Some(reverse) = self.reverse {
+            settings.reverse = reverse;
+        }
+        if let Some(start_position) = self.loop_start {
+            settings.loop_behavior = Some(LoopBehavior { start_position });
+        } else if self.looped && settings.loop_behavior.is_none() {
+            settings.loop_behavior = Some(LoopBehavior {
+                start_position: 0.0,
+            });
+        }
+        if let Some(fade_in) = self.fade_in.as_ref() {
+            settings.fade_in_tween = Some(fade_in.into());
+        }
+        if let Some(start_time) = self.start_time {
+            settings.start_time = StartTime::ClockTime(start_time);
+        }
+    }
+}
+
+/// A play command for an audio channel
+///
+/// Use the methods of this builder to configure the single playback.
+/// The command is added to the channel when this value is dropped, so commands for the instance
+/// have to be issued after the builder was dropped:
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::Audio;
+/// fn my_system(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+///     let play = audio.play(asset_server.load("audio.mp3")).looped();
+///     let instance_handle = play.handle();
+///     // queues the play; a stop before this line would run before the play and do nothing
+///     drop(play);
+///     audio.stop_instance(&instance_handle);
+/// }
+/// ```
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{Audio, AudioTween};
+/// # use std::time::Duration;
+///
+/// fn my_system(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+///     audio
+///         .play(asset_server.load("audio.mp3"))
+///         .with_volume(0.5)
+///         .with_panning(0.2)
+///         .fade_in(AudioTween::linear(Duration::from_secs(2)));
+/// }
+/// ```
+pub struct PlayAudioCommand<'a> {
+    instance_handle: InstanceHandle,
+    settings: Option<PlayAudioSettings>,
+    queue: &'a RwLock<VecDeque<AudioCommand>>,
+}
+
+impl<'a> PlayAudioCommand<'a> {
+    pub(crate) fn new(
+        source: Handle<AudioSource>,
+        queue: &'a RwLock<VecDeque<AudioCommand>>,
+    ) -> Self {
+        PlayAudioCommand {
+            instance_handle: InstanceHandle::new(),
+            settings: Some(PlayAudioSettings::new(source)),
+            queue,
+        }
+    }
+
+    fn settings(&mut self) -> &mut PlayAudioSettings {
+        self.settings
+            .as_mut()
+            .expect("Play command settings are only taken on drop")
+    }
+
+    /// Loop the sound
+    ///
+    /// If the sound does not define a loop behavior itself, it will loop from the beginning.
+    pub fn looped(mut self) -> Self {
+        self.settings().looped = true;
+        self
+    }
+
+    /// Loop the sound, jumping back to the given position (in seconds) at its end
+    pub fn loop_from(mut self, start_position: f64) -> Self {
+        let settings = self.settings();
+        settings.looped = true;
+        settings.loop_start = Some(start_position);
+        self
+    }
+
+    /// Loop the region between the given positions (in seconds)
+    ///
+    /// The sound plays from its start position and jumps back to `start` when it reaches `end`.
+    /// Only static sounds that do not play in reverse support a loop end. Other sounds loop at
+    /// their end. Use [`AudioChannel::disable_looping`](crate::AudioChannel::disable_looping) to
+    /// let the sound play out to its end.
+    pub fn loop_region(mut self, start: f64, end: f64) -> Self {
+        let settings = self.settings();
+        settings.looped = true;
+        settings.loop_start = Some(start);
+        settings.loop_end = Some(end);
+        self
+    }
+
+    /// Play the given sound once before the looped sound
+    pub(crate) fn with_intro(mut self, intro_source: Handle<AudioSource>) -> Self {
+        self.settings().intro_source = Some(intro_source);
+        self
+    }
+
+    /// Fade out the other sounds of the channel while fading in this one
+    pub(crate) fn crossfade(mut self, duration: Duration) -> Self {
+        self.settings().crossfade = Some(duration);
+        self
+    }
+
+    /// Start this playback on a grid position of a reference instance
+    pub(crate) fn quantize(mut self, quantize: QuantizedStart) -> Self {
+        self.settings().quantize = Some(quantize);
+        self
+    }
+
+    /// Set the volume of this playback
+    ///
+    /// The volume is relative to the volume of the channel.
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.settings().volume = Some(volume as f64);
+        self
+    }
+
+    /// Set the panning of this playback
+    ///
+    /// 0 is hard left and 1 is hard right. The panning of the channel is applied on top.
+    pub fn with_panning(mut self, panning: f32) -> Self {
+        self.settings().panning = Some(panning as f64);
+        self
+    }
+
+    /// Set the playback rate of this playback
+    ///
+    /// The playback rate is relative to the playback rate of the channel.
+    pub fn with_playback_rate(mut self, playback_rate: f32) -> Self {
+        self.settings().playback_rate = Some(playback_rate as f64);
+        self
+    }
+
+    /// Start the sound at the given position in seconds
+    pub fn start_from(mut self, start_position: f64) -> Self {
+        self.settings().start_position = Some(start_position);
+        self
+    }
+
+    /// Play the sound in reverse
+    ///
+    /// The start position will be relative to the end of the sound.
+    pub fn reverse(mut self) -> Self {
+        self.settings().reverse = Some(true);
+        self
+    }
+
+    /// Fade the sound in from silence
+    ///
+    /// A start delay of the tween delays the playback.
+    pub fn fade_in(mut self, tween: AudioTween) -> Self {
+        self.settings().fade_in = Some(tween);
+        self
+    }
+
+    /// Wait for the given duration before starting the playback
+    ///
+    /// The delay is counted down once per frame, so the playback starts in the first frame
+    /// after the delay passed. Use [`start_at`](Self::start_at) to start sample accurate.
+    pub fn with_start_delay(mut self, start_delay: Duration) -> Self {
+        self.settings().start_delay = start_delay;
+        self
+    }
+
+    /// Start the playback sample accurate when the clock reaches the given time
+    ///
+    /// The sound is added to the mixer right away and reports
+    /// [`PlaybackState::Playing`](crate::PlaybackState::Playing) at its start position until the
+    /// clock reaches the time. See [`AudioClock`](crate::AudioClock).
+    pub fn start_at(mut self, start_time: ClockTime) -> Self {
+        self.settings().start_time = Some(start_time);
+        self
+    }
+
+    /// Set the priority of this playback
+    ///
+    /// When the maximum number of sounds is playing, sounds are only stopped for new sounds with
+    /// the same or a higher priority. The default priority is 0.
+    /// See [`SoundLimitPolicy`](crate::SoundLimitPolicy).
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.settings().priority = priority;
+        self
+    }
+
+    /// Get the handle of the instance started by this command
+    pub fn handle(&self) -> InstanceHandle {
+        self.instance_handle.clone()
+    }
+}
+
+impl<'a> Drop for PlayAudioCommand<'a> {
+    fn drop(&mut self) {
+        if let Some(mut settings) = self.settings.take() {
+            if let Some(fade_in) = settings.fade_in.as_mut() {
+                settings.start_delay += fade_in.start_delay;
+                fade_in.start_delay = Duration::ZERO;
+            }
+            self.queue
+                .write()
+                .push_front(AudioCommand::Play(Box::new(PlayAudioCommandArgs {
+                    settings,
+                    instance_handle: self.instance_handle.clone(),
+                })));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::asset::HandleId;
+
+    #[test]
+    fn play_settings_are_applied_on_top_of_sound_settings() {
+        let mut settings = PlayAudioSettings::new(Handle::weak(HandleId::default::<AudioSource>()));
+        settings.volume = Some(0.5);
+        settings.looped = true;
+        let mut sound_settings = StaticSoundSettings::new().volume(0.5);
+
+        settings.apply(&mut sound_settings);
+
+        assert_eq!(sound_settings.volume.as_amplitude(), 0.25);
+        assert_eq!(
+            sound_settings.loop_behavior,
+            Some(LoopBehavior {
+                start_position: 0.0
+            })
+        );
+    }
+}
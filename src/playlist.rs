@@ -0,0 +1,306 @@
+use crate::audio::{AudioChannel, InstanceHandle, PlaybackState};
+use crate::source::AudioSource;
+use bevy::asset::Handle;
+use bevy::ecs::system::Resource;
+use bevy::prelude::ResMut;
+use std::time::Duration;
+
+/// A queue of tracks played one after another in an audio channel
+///
+/// Set the playlist of a channel with [`AudioChannel::set_playlist`]. The next track starts
+/// automatically when the current one ended. Tracks that loop never end, so only the first one
+/// would play.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{AudioChannel, Playlist, PlaylistMode};
+/// # use std::time::Duration;
+/// struct Music;
+///
+/// fn start_music(asset_server: Res<AssetServer>, mut music: ResMut<AudioChannel<Music>>) {
+///     music.set_playlist(Some(
+///         Playlist::new(vec![
+///             asset_server.load("music/first.ogg"),
+///             asset_server.load("music/second.ogg"),
+///         ])
+///         .with_mode(PlaylistMode::RepeatAll)
+///         .with_crossfade(Duration::from_secs(2)),
+///     ));
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Playlist {
+    tracks: Vec<Handle<AudioSource>>,
+    mode: PlaylistMode,
+    crossfade: Option<Duration>,
+    position: Option<usize>,
+    current: Option<InstanceHandle>,
+    /// Upcoming tracks of the current shuffle round in reverse order
+    shuffled: Vec<usize>,
+    finished: bool,
+}
+
+/// The order in which a [`Playlist`] plays its tracks
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PlaylistMode {
+    /// Play all tracks once in the given order
+    Sequential,
+    /// Play all tracks in random order, then reshuffle and start over
+    ///
+    /// The same track is never played twice in a row.
+    Shuffle,
+    /// Repeat the current track
+    RepeatOne,
+    /// Play all tracks in the given order, then start over
+    RepeatAll,
+}
+
+impl Default for PlaylistMode {
+    fn default() -> Self {
+        PlaylistMode::Sequential
+    }
+}
+
+impl Playlist {
+    /// Create a sequential playlist of the given tracks
+    pub fn new(tracks: Vec<Handle<AudioSource>>) -> Self {
+        Playlist {
+            tracks,
+            mode: PlaylistMode::default(),
+            crossfade: None,
+            position: None,
+            current: None,
+            shuffled: vec![],
+            finished: false,
+        }
+    }
+
+    /// Set the order in which tracks are played
+    pub fn with_mode(mut self, mode: PlaylistMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    /// Fade from one track to the next over the given duration
+    ///
+    /// The next track starts `crossfade` before the end of the current one.
+    /// Like [`AudioChannel::crossfade_to`], this fades out all other sounds in the channel.
+    /// Streamed tracks are not crossfaded, because their duration is unknown.
+    pub fn with_crossfade(mut self, crossfade: Duration) -> Self {
+        self.crossfade = Some(crossfade);
+        self
+    }
+
+    /// Change the order in which the following tracks are played
+    pub fn set_mode(&mut self, mode: PlaylistMode) {
+        self.mode = mode;
+        self.shuffled.clear();
+    }
+
+    /// Add a track to the end of the playlist
+    ///
+    /// A finished playlist continues with the new track.
+    pub fn push(&mut self, track: Handle<AudioSource>) {
+        self.tracks.push(track);
+        self.shuffled.clear();
+        if self.finished {
+            self.finished = false;
+            self.position = self.tracks.len().checked_sub(2);
+        }
+    }
+
+    /// The tracks of this playlist
+    pub fn tracks(&self) -> &[Handle<AudioSource>] {
+        &self.tracks
+    }
+
+    /// The order in which tracks are played
+    pub fn mode(&self) -> PlaylistMode {
+        self.mode
+    }
+
+    /// Index of the current track in [`tracks`](Self::tracks)
+    ///
+    /// `None` before the first track started and after the playlist finished.
+    pub fn position(&self) -> Option<usize> {
+        self.position.filter(|_| self.current.is_some())
+    }
+
+    /// The track that is currently playing
+    pub fn current_track(&self) -> Option<&Handle<AudioSource>> {
+        self.position().map(|position| &self.tracks[position])
+    }
+
+    /// The playback instance of the current track
+    pub fn current_instance(&self) -> Option<&InstanceHandle> {
+        self.current.as_ref()
+    }
+
+    /// Whether the playlist played all its tracks
+    ///
+    /// Only [`PlaylistMode::Sequential`] playlists finish.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Move to the track after the current one
+    fn advance(&mut self) -> Option<usize> {
+        let len = self.tracks.len();
+        let next = match (self.mode, self.position) {
+            _ if len == 0 => None,
+            (PlaylistMode::RepeatOne, Some(position)) if position < len => Some(position),
+            (PlaylistMode::Sequential, Some(position)) => {
+                Some(position + 1).filter(|next| *next < len)
+            }
+            (PlaylistMode::Shuffle, _) => {
+                if self.shuffled.is_empty() {
+                    self.shuffled = (0..len).collect();
+                    fastrand::shuffle(&mut self.shuffled);
+                    if len > 1 && self.shuffled.last() == self.position.as_ref() {
+                        self.shuffled.swap(0, len - 1);
+                    }
+                }
+                self.shuffled.pop()
+            }
+            (_, Some(position)) => Some((position + 1) % len),
+            (_, None) => Some(0),
+        };
+        self.position = next;
+        self.finished = next.is_none();
+        next
+    }
+}
+
+pub(crate) fn update_playlist<T: Resource>(mut channel: ResMut<AudioChannel<T>>) {
+    let mut playlist = match channel.playlist.take() {
+        Some(playlist) => playlist,
+        None => return,
+    };
+    let current_state = playlist
+        .current
+        .clone()
+        .map(|current| channel.state(current));
+    let start_next = match &current_state {
+        None => !playlist.finished,
+        Some(PlaybackState::Stopped | PlaybackState::Failed | PlaybackState::Suppressed) => true,
+        Some(_) => match (playlist.crossfade, &playlist.current) {
+            (Some(crossfade), Some(current)) => channel
+                .remaining(current)
+                .map_or(false, |remaining| remaining <= crossfade),
+            _ => false,
+        },
+    };
+    if start_next {
+        let crossfade = playlist
+            .crossfade
+            .filter(|_| matches!(current_state, Some(PlaybackState::Playing { .. })));
+        playlist.current = playlist.advance().map(|next| {
+            let track = playlist.tracks[next].clone();
+            match crossfade {
+                Some(crossfade) => channel.crossfade_to(track, crossfade).handle(),
+                None => channel.play(track).handle(),
+            }
+        });
+    }
+    channel.playlist = Some(playlist);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MainTrack;
+    use bevy::asset::HandleId;
+    use bevy::prelude::App;
+
+    fn playlist(tracks: usize, mode: PlaylistMode) -> Playlist {
+        Playlist::new(
+            (0..tracks)
+                .map(|_| Handle::weak(HandleId::random::<AudioSource>()))
+                .collect(),
+        )
+        .with_mode(mode)
+    }
+
+    fn positions(playlist: &mut Playlist, count: usize) -> Vec<Option<usize>> {
+        (0..count).map(|_| playlist.advance()).collect()
+    }
+
+    #[test]
+    fn sequential_playlists_finish_after_the_last_track() {
+        let mut playlist = playlist(2, PlaylistMode::Sequential);
+
+        assert_eq!(positions(&mut playlist, 3), vec![Some(0), Some(1), None]);
+        assert!(playlist.is_finished());
+
+        playlist.push(Handle::weak(HandleId::random::<AudioSource>()));
+        assert_eq!(playlist.advance(), Some(2));
+    }
+
+    #[test]
+    fn repeating_playlists_start_over() {
+        let mut repeat_all = playlist(2, PlaylistMode::RepeatAll);
+        let mut repeat_one = playlist(2, PlaylistMode::RepeatOne);
+
+        assert_eq!(
+            positions(&mut repeat_all, 4),
+            vec![Some(0), Some(1), Some(0), Some(1)]
+        );
+        assert_eq!(
+            positions(&mut repeat_one, 3),
+            vec![Some(0), Some(0), Some(0)]
+        );
+    }
+
+    #[test]
+    fn shuffled_playlists_play_every_track_once_per_round() {
+        let mut playlist = playlist(3, PlaylistMode::Shuffle);
+
+        let mut last = None;
+        for _ in 0..10 {
+            let mut round: Vec<usize> = positions(&mut playlist, 3)
+                .into_iter()
+                .map(Option::unwrap)
+                .collect();
+            assert_ne!(Some(round[0]), last);
+            last = round.last().copied();
+            round.sort_unstable();
+            assert_eq!(round, vec![0, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn next_track_is_played_when_the_current_one_stopped() {
+        let mut app = App::new();
+        app.add_system(update_playlist::<MainTrack>)
+            .insert_resource(AudioChannel::<MainTrack>::default());
+        app.world
+            .resource_mut::<AudioChannel<MainTrack>>()
+            .set_playlist(Some(playlist(2, PlaylistMode::Sequential)));
+
+        app.update();
+        let channel = app.world.resource::<AudioChannel<MainTrack>>();
+        let first = channel
+            .playlist()
+            .unwrap()
+            .current_instance()
+            .cloned()
+            .unwrap();
+        assert_eq!(channel.state(first.clone()), PlaybackState::Queued);
+        assert_eq!(channel.playlist().unwrap().position(), Some(0));
+
+        // The first track stopped without ever playing
+        app.world
+            .resource::<AudioChannel<MainTrack>>()
+            .commands
+            .write()
+            .clear();
+        app.update();
+        let playlist = app
+            .world
+            .resource::<AudioChannel<MainTrack>>()
+            .playlist()
+            .unwrap();
+        assert_eq!(playlist.position(), Some(1));
+        assert_ne!(playlist.current_instance(), Some(&first));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::audio::InstanceHandle;
+use crate::tempo::Tempo;
+
+/// The musical grid a quantized play starts on
+///
+/// See [`AudioChannel::play_quantized`](crate::AudioChannel::play_quantized).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Quantize {
+    /// Start on the next beat of the reference instance
+    NextBeat,
+    /// Start on the first beat of the next bar of the reference instance
+    NextBar,
+    /// Start on the next multiple of the given number of beats
+    ///
+    /// For example, `Grid(0.5)` starts on the next eighth note in 4/4 time and `Grid(8.)` on
+    /// the next beat that is a multiple of 8.
+    Grid(f64),
+}
+
+impl Quantize {
+    /// Distance between two grid positions in beats
+    fn step(&self, tempo: &Tempo) -> f64 {
+        match self {
+            Quantize::NextBeat => 1.,
+            Quantize::NextBar => f64::from(tempo.beats_per_bar.max(1)),
+            Quantize::Grid(beats) => *beats,
+        }
+    }
+
+    /// The first grid position after the given beat
+    pub(crate) fn next_beat(&self, tempo: &Tempo, beat: f64) -> f64 {
+        let step = self.step(tempo);
+        ((beat / step).floor() + 1.) * step
+    }
+}
+
+/// A playing instance whose beats quantized plays of a channel are aligned to
+///
+/// Usually this is the current music, which can play in another channel.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{Audio, AudioChannel, InstanceHandle, Quantize, QuantizeReference, Tempo};
+/// struct Music;
+/// struct CurrentMusic(InstanceHandle);
+///
+/// fn start_music(
+///     mut commands: Commands,
+///     asset_server: Res<AssetServer>,
+///     music: Res<AudioChannel<Music>>,
+///     mut audio: ResMut<Audio>,
+/// ) {
+///     let instance = music.play(asset_server.load("music.ogg")).looped().handle();
+///     audio.set_quantize_reference(Some(
+///         QuantizeReference::new(instance.clone()).with_tempo(Tempo::new(128.).with_offset(0.2)),
+///     ));
+///     commands.insert_resource(CurrentMusic(instance));
+/// }
+///
+/// fn play_victory_stinger(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+///     audio.play_quantized(asset_server.load("victory.ogg"), Quantize::NextBar);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantizeReference {
+    /// The reference instance
+    pub instance: InstanceHandle,
+    /// The tempo of the reference instance
+    ///
+    /// If `None`, the [`tempo`](crate::AudioSource::tempo) of the played audio source is used.
+    pub tempo: Option<Tempo>,
+}
+
+impl QuantizeReference {
+    /// Align to the given instance with the tempo of its audio source
+    pub fn new(instance: InstanceHandle) -> Self {
+        QuantizeReference {
+            instance,
+            tempo: None,
+        }
+    }
+
+    /// Align to the beats of the given tempo instead of the tempo of the audio source
+    pub fn with_tempo(mut self, tempo: Tempo) -> Self {
+        self.tempo = Some(tempo);
+        self
+    }
+}
+
+/// A play waiting for a grid position of its reference instance
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct QuantizedStart {
+    pub(crate) quantize: Quantize,
+    pub(crate) reference: QuantizeReference,
+    /// The beat of the reference instance to start on
+    target: Option<f64>,
+}
+
+impl QuantizedStart {
+    pub(crate) fn new(quantize: Quantize, reference: QuantizeReference) -> Self {
+        QuantizedStart {
+            quantize,
+            reference,
+            target: None,
+        }
+    }
+
+    /// Seconds until the reference instance reaches the target grid position
+    ///
+    /// `position` is the position of the reference instance and `playback_rate` its effective
+    /// playback rate. The target is chosen on the first call and again when the reference
+    /// jumped back before the previous grid position (e.g. when looping).
+    pub(crate) fn time_until_start(
+        &mut self,
+        tempo: &Tempo,
+        position: f64,
+        playback_rate: f64,
+    ) -> f64 {
+        let step = self.quantize.step(tempo);
+        let valid = step > 0. && tempo.bpm > 0.;
+        if !valid {
+            return 0.;
+        }
+        let beat = tempo.beat_at(position);
+        let target = match self.target {
+            Some(target) if beat >= target - step => target,
+            _ => self.quantize.next_beat(tempo, beat),
+        };
+        self.target = Some(target);
+        (target - beat).max(0.) * tempo.beat_duration() / playback_rate.abs().max(f64::EPSILON)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quantized_starts_wait_for_the_next_grid_position() {
+        let tempo = Tempo::new(60.).with_offset(0.5);
+        let mut next_bar = QuantizedStart::new(
+            Quantize::NextBar,
+            QuantizeReference::new(InstanceHandle::new()),
+        );
+        let mut grid = QuantizedStart::new(
+            Quantize::Grid(0.5),
+            QuantizeReference::new(InstanceHandle::new()),
+        );
+
+        assert!((next_bar.time_until_start(&tempo, 1.7, 1.) - 2.8).abs() < 1e-9);
+        assert!((next_bar.time_until_start(&tempo, 4.1, 2.) - 0.2).abs() < 1e-9);
+        assert_eq!(next_bar.time_until_start(&tempo, 4.6, 1.), 0.);
+        // the reference looped back before the first beat
+        assert!((next_bar.time_until_start(&tempo, 0.2, 1.) - 0.3).abs() < 1e-9);
+
+        assert!((grid.time_until_start(&tempo, 1.7, 1.) - 0.3).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,172 @@
+use kira::dsp::Frame;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Audio rendered offline with the [`AudioBackend::Mock`](crate::AudioBackend::Mock) backend
+///
+/// Use [`AudioOutput::render`](crate::AudioOutput::render) to render the mix of all channels.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{AudioOutput, AudioRecording};
+/// # use std::time::Duration;
+/// fn record(mut audio_output: NonSendMut<AudioOutput>, mut recording: ResMut<AudioRecording>) {
+///     if let Some(rendered) = audio_output.render(Duration::from_secs_f64(1. / 60.)) {
+///         if let Err(error) = recording.append(rendered) {
+///             error!("{}", error);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioRecording {
+    /// Number of frames per second
+    pub sample_rate: u32,
+    /// Stereo frames of the main output
+    pub frames: Vec<Frame>,
+}
+
+impl AudioRecording {
+    /// Create an empty recording
+    pub fn new(sample_rate: u32) -> Self {
+        AudioRecording {
+            sample_rate,
+            frames: vec![],
+        }
+    }
+
+    /// Add the frames of another recording with the same sample rate at the end of this one
+    ///
+    /// Recordings with a different sample rate are not added.
+    pub fn append(&mut self, mut other: AudioRecording) -> Result<(), SampleRateMismatch> {
+        if self.sample_rate != other.sample_rate {
+            return Err(SampleRateMismatch {
+                expected: self.sample_rate,
+                found: other.sample_rate,
+            });
+        }
+        self.frames.append(&mut other.frames);
+
+        Ok(())
+    }
+
+    /// Write the recording in WAV format (16 bit PCM)
+    ///
+    /// Samples outside of -1 to 1 are clipped.
+    pub fn write_wav(&self, mut writer: impl Write) -> std::io::Result<()> {
+        const CHANNELS: u16 = 2;
+        const BITS_PER_SAMPLE: u16 = 16;
+        let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+        let data_size = self.frames.len() as u32 * block_align as u32;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data_size).to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        // PCM
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&CHANNELS.to_le_bytes())?;
+        writer.write_all(&self.sample_rate.to_le_bytes())?;
+        writer.write_all(&(self.sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&data_size.to_le_bytes())?;
+        for frame in self.frames.iter() {
+            writer.write_all(&pcm_sample(frame.left).to_le_bytes())?;
+            writer.write_all(&pcm_sample(frame.right).to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+
+    /// Save the recording as WAV file (16 bit PCM)
+    pub fn save_wav(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        self.write_wav(BufWriter::new(File::create(path)?))
+    }
+}
+
+/// Error appending a recording with another sample rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleRateMismatch {
+    /// Sample rate of the recording that was appended to
+    pub expected: u32,
+    /// Sample rate of the appended recording
+    pub found: u32,
+}
+
+impl fmt::Display for SampleRateMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cannot append a recording with sample rate {} to one with sample rate {}",
+            self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for SampleRateMismatch {}
+
+fn pcm_sample(sample: f32) -> i16 {
+    (sample.clamp(-1., 1.) * i16::MAX as f32).round() as i16
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recording_is_written_as_wav() {
+        let recording = AudioRecording {
+            sample_rate: 8000,
+            frames: vec![Frame::new(1., -2.), Frame::new(0., 0.5)],
+        };
+        let mut wav = vec![];
+
+        recording.write_wav(&mut wav).unwrap();
+
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[4..8], &44u32.to_le_bytes());
+        assert_eq!(&wav[24..28], &8000u32.to_le_bytes());
+        assert_eq!(&wav[40..44], &8u32.to_le_bytes());
+        assert_eq!(&wav[44..46], &i16::MAX.to_le_bytes());
+        assert_eq!(&wav[46..48], &(-i16::MAX).to_le_bytes());
+        assert_eq!(&wav[50..52], &16384i16.to_le_bytes());
+    }
+
+    #[test]
+    fn only_recordings_with_the_same_sample_rate_are_appended() {
+        let mut recording = AudioRecording {
+            sample_rate: 100,
+            frames: vec![Frame::from_mono(1.)],
+        };
+
+        let result = recording.append(AudioRecording {
+            sample_rate: 200,
+            frames: vec![Frame::from_mono(0.5)],
+        });
+        assert_eq!(
+            result,
+            Err(SampleRateMismatch {
+                expected: 100,
+                found: 200
+            })
+        );
+        assert_eq!(recording.frames.len(), 1);
+
+        recording
+            .append(AudioRecording {
+                sample_rate: 100,
+                frames: vec![Frame::from_mono(0.5)],
+            })
+            .unwrap();
+        assert_eq!(
+            recording.frames,
+            vec![Frame::from_mono(1.), Frame::from_mono(0.5)]
+        );
+    }
+}
@@ -0,0 +1,889 @@
+use crate::audio::{AudioChannel, InstanceHandle, PlaybackState};
+use crate::audio_output::AudioOutput;
+use crate::clock::AudioClock;
+use crate::quantize::Quantize;
+use crate::source::{AudioSource, AudioSourceData};
+use crate::tempo::Tempo;
+use crate::tween::AudioTween;
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::system::Resource;
+use bevy::log::warn;
+use bevy::prelude::{NonSendMut, Res, ResMut, Time};
+use bevy::reflect::TypeUuid;
+use kira::clock::ClockTime;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Tempo of the clock used to schedule transitions
+const TICKS_PER_SECOND: f64 = 1000.;
+
+/// Music segments and the rules to transition between them
+///
+/// A [`MusicSequencer`] plays the segments of a sequence and switches between them following
+/// the transitions. Sequences can be loaded from `*.sequence.ron` files (requires the feature
+/// `settings_loader`) or added to the [`Assets<MusicSequence>`](bevy::asset::Assets) in code.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{MusicSegment, MusicSequence, MusicTransition, Tempo, TransitionTiming};
+/// # use std::time::Duration;
+/// fn music_sequence(asset_server: &AssetServer) -> MusicSequence {
+///     MusicSequence::default()
+///         .with_segment(
+///             "explore",
+///             MusicSegment::new(asset_server.load("music/explore.ogg"))
+///                 .with_tempo(Tempo::new(100.))
+///                 .with_exit_markers(vec![9.6, 19.2]),
+///         )
+///         .with_segment("combat", MusicSegment::new(asset_server.load("music/combat.ogg")))
+///         .with_segment(
+///             "alarm",
+///             MusicSegment::new(asset_server.load("music/alarm.ogg")).not_looped(),
+///         )
+///         .with_transition(
+///             MusicTransition::new("combat", TransitionTiming::NextBar)
+///                 .from("explore")
+///                 .via("alarm"),
+///         )
+///         .with_transition(
+///             MusicTransition::new("explore", TransitionTiming::ExitMarker)
+///                 .with_fade(Duration::from_secs(2)),
+///         )
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, TypeUuid)]
+#[uuid = "15c052b6-ca4d-4a6f-a59d-1e6f4750c1bb"]
+pub struct MusicSequence {
+    /// The segments by name
+    pub segments: HashMap<String, MusicSegment>,
+    /// Rules for switching from one segment to another
+    ///
+    /// Switching to a segment without a matching transition happens immediately without fade.
+    pub transitions: Vec<MusicTransition>,
+}
+
+/// A part of a [`MusicSequence`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MusicSegment {
+    /// The sound of this segment
+    pub source: Handle<AudioSource>,
+    /// Whether the segment loops until the next transition
+    ///
+    /// Segments played as transition segments never loop.
+    pub looped: bool,
+    /// The tempo used for transitions on the next beat or bar
+    ///
+    /// If `None`, the [`tempo`](crate::AudioSource::tempo) of the audio source is used.
+    pub tempo: Option<Tempo>,
+    /// Positions in seconds at which [`TransitionTiming::ExitMarker`] transitions can leave the
+    /// segment
+    pub exit_markers: Vec<f64>,
+}
+
+/// A rule for switching between two segments of a [`MusicSequence`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MusicTransition {
+    /// The segment to switch from; `None` matches all segments
+    ///
+    /// Transitions from a specific segment take precedence.
+    pub from: Option<String>,
+    /// The segment to switch to
+    pub to: String,
+    /// When to leave the current segment
+    pub timing: TransitionTiming,
+    /// A segment played once between the current segment and the target segment
+    pub via: Option<String>,
+    /// Crossfade from the current segment instead of switching on the same frame
+    pub fade: Option<Duration>,
+}
+
+/// When a [`MusicTransition`] leaves the current segment
+///
+/// Transitions that wait for a position of the current segment switch at the end of the
+/// segment if it ends or loops first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "settings_loader", derive(serde::Deserialize))]
+pub enum TransitionTiming {
+    /// Switch right away
+    Immediate,
+    /// Switch on the next beat of the current segment
+    NextBeat,
+    /// Switch on the next bar of the current segment
+    NextBar,
+    /// Switch at the next exit marker of the current segment
+    ExitMarker,
+}
+
+impl Default for TransitionTiming {
+    fn default() -> Self {
+        TransitionTiming::Immediate
+    }
+}
+
+impl MusicSequence {
+    /// Add a segment with the given name
+    pub fn with_segment(mut self, name: impl Into<String>, segment: MusicSegment) -> Self {
+        self.segments.insert(name.into(), segment);
+        self
+    }
+
+    /// Add a transition rule
+    pub fn with_transition(mut self, transition: MusicTransition) -> Self {
+        self.transitions.push(transition);
+        self
+    }
+
+    /// The transition to use for switching between the given segments
+    pub fn transition(&self, from: &str, to: &str) -> MusicTransition {
+        let matching = |from: Option<&str>| {
+            self.transitions
+                .iter()
+                .find(|transition| transition.from.as_deref() == from && transition.to == to)
+        };
+        matching(Some(from))
+            .or_else(|| matching(None))
+            .cloned()
+            .unwrap_or_else(|| MusicTransition::new(to, TransitionTiming::Immediate))
+    }
+}
+
+impl MusicSegment {
+    /// Create a looping segment without tempo or exit markers
+    pub fn new(source: Handle<AudioSource>) -> Self {
+        MusicSegment {
+            source,
+            looped: true,
+            tempo: None,
+            exit_markers: vec![],
+        }
+    }
+
+    /// Play the segment only once
+    pub fn not_looped(mut self) -> Self {
+        self.looped = false;
+        self
+    }
+
+    /// Set the tempo used for transitions on the next beat or bar
+    pub fn with_tempo(mut self, tempo: Tempo) -> Self {
+        self.tempo = Some(tempo);
+        self
+    }
+
+    /// Set the positions in seconds at which transitions can leave the segment
+    pub fn with_exit_markers(mut self, exit_markers: Vec<f64>) -> Self {
+        self.exit_markers = exit_markers;
+        self
+    }
+}
+
+impl MusicTransition {
+    /// Create a transition from any segment to the given one without fade
+    pub fn new(to: impl Into<String>, timing: TransitionTiming) -> Self {
+        MusicTransition {
+            from: None,
+            to: to.into(),
+            timing,
+            via: None,
+            fade: None,
+        }
+    }
+
+    /// Only use this transition when switching from the given segment
+    pub fn from(mut self, from: impl Into<String>) -> Self {
+        self.from = Some(from.into());
+        self
+    }
+
+    /// Play the given segment once before the target segment
+    pub fn via(mut self, via: impl Into<String>) -> Self {
+        self.via = Some(via.into());
+        self
+    }
+
+    /// Crossfade from the current segment over the given duration
+    pub fn with_fade(mut self, fade: Duration) -> Self {
+        self.fade = Some(fade);
+        self
+    }
+}
+
+/// Plays the segments of a [`MusicSequence`] in an audio channel
+///
+/// Set the sequencer of a channel with [`AudioChannel::set_music_sequencer`] and request
+/// segments with [`request`](Self::request). Transitions with a fade crossfade out all other
+/// sounds in the channel, so sequences should play in their own channel.
+///
+/// Transitions from static sounds are scheduled sample accurate on a clock of the
+/// [`AudioOutput`]. The clock keeps running while the channel is paused and does not follow
+/// the playback rate of the channel, so pausing or changing the playback rate shifts
+/// scheduled transitions. Without an audio output or for streaming sounds, the sequencer
+/// switches in the frame closest to the transition point.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{AudioChannel, MusicSequencer};
+/// struct Music;
+///
+/// fn start_music(asset_server: Res<AssetServer>, mut music: ResMut<AudioChannel<Music>>) {
+///     let mut sequencer = MusicSequencer::new(asset_server.load("music/level.sequence.ron"));
+///     sequencer.request("explore");
+///     music.set_music_sequencer(Some(sequencer));
+/// }
+///
+/// fn enter_combat(mut music: ResMut<AudioChannel<Music>>) {
+///     if let Some(sequencer) = music.music_sequencer_mut() {
+///         sequencer.request("combat");
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MusicSequencer {
+    sequence: Handle<MusicSequence>,
+    requested: Option<String>,
+    current: Option<PlayingSegment>,
+    transition: Option<PendingTransition>,
+    clock: Option<AudioClock>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct PlayingSegment {
+    name: String,
+    instance: InstanceHandle,
+    last_position: Option<f64>,
+    /// Clock tick at which the segment starts
+    start: Option<u64>,
+    /// Whether the segment plays looped
+    looped: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct PendingTransition {
+    transition: MusicTransition,
+    /// Position of the current segment to switch at; `None` switches at its end
+    switch_at: Option<f64>,
+    /// Whether the current segment is the transition segment
+    playing_via: bool,
+    /// The segment scheduled on the clock to replace the current segment
+    next: Option<PlayingSegment>,
+}
+
+impl MusicSequencer {
+    /// Create a sequencer that waits for the first request
+    pub fn new(sequence: Handle<MusicSequence>) -> Self {
+        MusicSequencer {
+            sequence,
+            requested: None,
+            current: None,
+            transition: None,
+            clock: None,
+        }
+    }
+
+    /// Switch to the segment with the given name following the transition rules
+    ///
+    /// A newer request replaces a transition that is not scheduled on the clock and did not
+    /// leave the current segment yet. Otherwise, the newer request is handled after the
+    /// scheduled segment or the target of the transition segment started.
+    pub fn request(&mut self, segment: impl Into<String>) {
+        self.requested = Some(segment.into());
+    }
+
+    /// The sequence played by this sequencer
+    pub fn sequence(&self) -> &Handle<MusicSequence> {
+        &self.sequence
+    }
+
+    /// Name of the playing segment, which can be a transition segment
+    pub fn current_segment(&self) -> Option<&str> {
+        self.current.as_ref().map(|current| current.name.as_str())
+    }
+
+    /// The playback instance of the playing segment
+    pub fn current_instance(&self) -> Option<&InstanceHandle> {
+        self.current.as_ref().map(|current| &current.instance)
+    }
+
+    /// Whether the sequencer waits to leave the current segment or plays a transition segment
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    fn update<T>(
+        &mut self,
+        channel: &AudioChannel<T>,
+        sequence: &MusicSequence,
+        audio_sources: Option<&Assets<AudioSource>>,
+        half_frame: f64,
+    ) {
+        let now = self.clock.as_ref().map(|clock| clock.time());
+        let next_tick = now.map(|now| now + 1);
+        let current = match self.current.as_mut() {
+            Some(current) => current,
+            None => {
+                if let Some(requested) = self.requested.take() {
+                    self.current =
+                        play_segment(channel, sequence, requested, None, true, None, next_tick);
+                }
+                return;
+            }
+        };
+
+        if let Some(pending) = self.transition.as_mut() {
+            match (&pending.next, now) {
+                (Some(next), Some(now)) if next.start.map_or(true, |start| now.ticks >= start) => {
+                    let next = pending.next.take().unwrap();
+                    let via_length = segment_length(sequence, &next.name, audio_sources);
+                    let start = next.start;
+                    *current = next;
+                    if pending.transition.via.is_some() && !pending.playing_via {
+                        let target = pending.transition.to.clone();
+                        let start_time = match (start, via_length) {
+                            (Some(start), Some(length)) => Some(ClockTime {
+                                ticks: (start + (length * TICKS_PER_SECOND).round() as u64)
+                                    .max(now.ticks + 1),
+                                ..now
+                            }),
+                            _ => None,
+                        };
+                        let next = start_time.and_then(|start_time| {
+                            play_segment(
+                                channel,
+                                sequence,
+                                target.clone(),
+                                None,
+                                true,
+                                None,
+                                Some(start_time),
+                            )
+                        });
+                        *pending = PendingTransition {
+                            transition: MusicTransition::new(target, TransitionTiming::Immediate),
+                            switch_at: None,
+                            playing_via: true,
+                            next,
+                        };
+                    } else {
+                        self.transition = None;
+                    }
+                    return;
+                }
+                (Some(_), _) => return,
+                (None, _) => {}
+            }
+        }
+
+        let state = channel.state(current.instance.clone());
+        let last_position = current.last_position;
+        current.last_position = state.position();
+        let current_name = current.name.clone();
+
+        if self
+            .transition
+            .as_ref()
+            .map_or(false, |pending| pending.playing_via)
+        {
+            let ended = match &state {
+                PlaybackState::Playing { .. } => channel
+                    .remaining(&current.instance)
+                    .map_or(false, |remaining| remaining.as_secs_f64() <= half_frame),
+                PlaybackState::Queued
+                | PlaybackState::Paused { .. }
+                | PlaybackState::Pausing { .. } => false,
+                PlaybackState::Stopping { .. }
+                | PlaybackState::Stopped
+                | PlaybackState::Failed
+                | PlaybackState::Suppressed => true,
+            };
+            if ended {
+                let target = self.transition.take().unwrap().transition.to;
+                if let Some(next) =
+                    play_segment(channel, sequence, target, None, true, None, next_tick)
+                {
+                    self.current = Some(next);
+                }
+            }
+            return;
+        }
+
+        let length = segment_length(sequence, &current_name, audio_sources);
+        // position and start of the current loop from the clock, if the segment started on it
+        let clock_position = match (now, current.start, length) {
+            (Some(now), Some(start), Some(length)) if length > 0. => {
+                let elapsed = now.ticks.saturating_sub(start) as f64 / TICKS_PER_SECOND;
+                let loop_start = if current.looped {
+                    (elapsed / length).floor() * length
+                } else {
+                    0.
+                };
+                Some((
+                    elapsed - loop_start,
+                    start as f64 + loop_start * TICKS_PER_SECOND,
+                ))
+            }
+            _ => None,
+        };
+
+        if let Some(requested) = self.requested.take() {
+            let pending_target = self
+                .transition
+                .as_ref()
+                .map(|pending| &pending.transition.to);
+            if requested == current_name {
+                self.transition = None;
+            } else if pending_target != Some(&requested) {
+                if !sequence.segments.contains_key(&requested) {
+                    warn!("Music sequence has no segment '{}'", requested);
+                    return;
+                }
+                let transition = sequence.transition(&current_name, &requested);
+                let position = clock_position
+                    .map(|(position, _)| position)
+                    .or_else(|| state.position())
+                    .unwrap_or(0.);
+                let switch_at = sequence.segments.get(&current_name).and_then(|segment| {
+                    switch_position(segment, &transition, position, audio_sources)
+                });
+                self.transition = Some(PendingTransition {
+                    transition,
+                    switch_at,
+                    playing_via: false,
+                    next: None,
+                });
+            }
+        }
+
+        let pending = match self.transition.as_ref() {
+            Some(pending) => pending,
+            None => return,
+        };
+        let current = self.current.as_ref().unwrap();
+
+        if let (Some(now), Some((_, loop_start)), Some(length)) = (now, clock_position, length) {
+            let switch_position = pending.switch_at.unwrap_or(length).min(length);
+            let switch_tick = ((loop_start + switch_position * TICKS_PER_SECOND).round() as u64)
+                .max(now.ticks + 1);
+            let transition = &pending.transition;
+            let (name, loop_segment) = match &transition.via {
+                Some(via) => (via.clone(), false),
+                None => (transition.to.clone(), true),
+            };
+            let next = play_segment(
+                channel,
+                sequence,
+                name,
+                transition.fade,
+                loop_segment,
+                Some(&current.instance),
+                Some(ClockTime {
+                    ticks: switch_tick,
+                    ..now
+                }),
+            );
+            match next {
+                Some(next) => self.transition.as_mut().unwrap().next = Some(next),
+                None => self.transition = None,
+            }
+            return;
+        }
+
+        let switch = match state {
+            PlaybackState::Playing { position } => {
+                let looped = last_position.map_or(false, |last_position| position < last_position);
+                looped
+                    || pending
+                        .switch_at
+                        .map_or(false, |switch_at| position + half_frame >= switch_at)
+            }
+            PlaybackState::Queued
+            | PlaybackState::Paused { .. }
+            | PlaybackState::Pausing { .. } => false,
+            PlaybackState::Stopping { .. }
+            | PlaybackState::Stopped
+            | PlaybackState::Failed
+            | PlaybackState::Suppressed => true,
+        };
+        if switch {
+            let transition = self.transition.take().unwrap().transition;
+            let stop = Some(&current.instance);
+            match transition.via {
+                Some(via) => {
+                    let next = play_segment(
+                        channel,
+                        sequence,
+                        via,
+                        transition.fade,
+                        false,
+                        stop,
+                        next_tick,
+                    );
+                    if next.is_some() {
+                        self.current = next;
+                        self.transition = Some(PendingTransition {
+                            transition: MusicTransition::new(
+                                transition.to,
+                                TransitionTiming::Immediate,
+                            ),
+                            switch_at: None,
+                            playing_via: true,
+                            next: None,
+                        });
+                    }
+                }
+                None => {
+                    if let Some(next) = play_segment(
+                        channel,
+                        sequence,
+                        transition.to,
+                        transition.fade,
+                        true,
+                        stop,
+                        next_tick,
+                    ) {
+                        self.current = Some(next);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Play the given segment, replacing the `stop` instance
+///
+/// Transition segments are played without looping. With a start time, the segment and the
+/// stop of the replaced instance are scheduled on the clock.
+fn play_segment<T>(
+    channel: &AudioChannel<T>,
+    sequence: &MusicSequence,
+    name: String,
+    fade: Option<Duration>,
+    loop_segment: bool,
+    stop: Option<&InstanceHandle>,
+    start_time: Option<ClockTime>,
+) -> Option<PlayingSegment> {
+    let segment = match sequence.segments.get(&name) {
+        Some(segment) => segment,
+        None => {
+            warn!("Music sequence has no segment '{}'", name);
+            return None;
+        }
+    };
+    let command = match start_time {
+        Some(start_time) => {
+            let tween = fade
+                .map_or_else(AudioTween::default, AudioTween::linear)
+                .with_start_time(start_time);
+            if let Some(stop) = stop {
+                channel.stop_instance(stop).with_tween(tween);
+            }
+            let command = channel.play(segment.source.clone()).start_at(start_time);
+            if fade.is_some() {
+                command.fade_in(tween)
+            } else {
+                command
+            }
+        }
+        None => match fade {
+            Some(fade) => channel.crossfade_to(segment.source.clone(), fade),
+            None => {
+                if let Some(stop) = stop {
+                    channel.stop_instance(stop);
+                }
+                channel.play(segment.source.clone())
+            }
+        },
+    };
+    let looped = loop_segment && segment.looped;
+    let command = if looped { command.looped() } else { command };
+    Some(PlayingSegment {
+        name,
+        instance: command.handle(),
+        last_position: None,
+        start: start_time.map(|start_time| start_time.ticks),
+        looped,
+    })
+}
+
+/// Length in seconds of the static sound of the given segment, or of its loop if it has a
+/// loop end
+fn segment_length(
+    sequence: &MusicSequence,
+    name: &str,
+    audio_sources: Option<&Assets<AudioSource>>,
+) -> Option<f64> {
+    let segment = sequence.segments.get(name)?;
+    let source = audio_sources?.get(&segment.source)?;
+    match &source.sound {
+        AudioSourceData::Static(sound) => {
+            let duration = sound.duration().as_secs_f64();
+            Some(
+                source
+                    .loop_end
+                    .map_or(duration, |loop_end| loop_end.min(duration)),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// Position of the current segment at which the transition leaves it
+///
+/// `None` waits for the end of the segment.
+fn switch_position(
+    segment: &MusicSegment,
+    transition: &MusicTransition,
+    position: f64,
+    audio_sources: Option<&Assets<AudioSource>>,
+) -> Option<f64> {
+    let quantize = match transition.timing {
+        TransitionTiming::Immediate => return Some(position),
+        TransitionTiming::ExitMarker => {
+            return segment
+                .exit_markers
+                .iter()
+                .copied()
+                .filter(|exit_marker| *exit_marker > position)
+                .reduce(f64::min)
+        }
+        TransitionTiming::NextBeat => Quantize::NextBeat,
+        TransitionTiming::NextBar => Quantize::NextBar,
+    };
+    let tempo = segment.tempo.or_else(|| {
+        audio_sources
+            .and_then(|audio_sources| audio_sources.get(&segment.source))
+            .and_then(|source| source.tempo)
+    });
+    match tempo {
+        Some(tempo) if tempo.bpm > 0. => {
+            let beat = quantize.next_beat(&tempo, tempo.beat_at(position));
+            Some(tempo.offset + beat * tempo.beat_duration())
+        }
+        _ => {
+            warn!("Music segment has no tempo; the transition happens right away");
+            Some(position)
+        }
+    }
+}
+
+pub(crate) fn update_music_sequencer<T: Resource>(
+    mut channel: ResMut<AudioChannel<T>>,
+    sequences: Option<Res<Assets<MusicSequence>>>,
+    audio_sources: Option<Res<Assets<AudioSource>>>,
+    audio_output: Option<NonSendMut<AudioOutput>>,
+    time: Option<Res<Time>>,
+) {
+    let mut sequencer = match channel.music_sequencer.take() {
+        Some(sequencer) => sequencer,
+        None => return,
+    };
+    let sequence = sequences
+        .as_ref()
+        .and_then(|sequences| sequences.get(&sequencer.sequence));
+    if let Some(sequence) = sequence {
+        if sequencer.clock.is_none() && sequencer.current.is_none() && sequencer.requested.is_some()
+        {
+            sequencer.clock = audio_output
+                .and_then(|mut audio_output| audio_output.add_clock(TICKS_PER_SECOND * 60.));
+            if let Some(clock) = sequencer.clock.as_ref() {
+                clock.start();
+            }
+        }
+        // switch in the frame closest to the transition point if there is no clock
+        let half_frame = time.map_or(0., |time| time.delta_seconds_f64() / 2.);
+        sequencer.update(&channel, sequence, audio_sources.as_deref(), half_frame);
+    }
+    channel.music_sequencer = Some(sequencer);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::audio::AudioCommand;
+    use crate::audio_output::test::{mock_output, source, test_app};
+    use crate::MainTrack;
+    use bevy::asset::{AddAsset, HandleId};
+    use bevy::prelude::{default, App};
+    use kira::dsp::Frame;
+
+    fn segment() -> MusicSegment {
+        MusicSegment::new(Handle::weak(HandleId::random::<AudioSource>()))
+    }
+
+    fn sequence() -> MusicSequence {
+        MusicSequence::default()
+            .with_segment("explore", segment().with_tempo(Tempo::new(60.)))
+            .with_segment("combat", segment())
+            .with_segment("alarm", segment().not_looped())
+            .with_transition(
+                MusicTransition::new("combat", TransitionTiming::NextBar)
+                    .from("explore")
+                    .via("alarm"),
+            )
+            .with_transition(MusicTransition::new("combat", TransitionTiming::ExitMarker))
+    }
+
+    fn app(sequence: MusicSequence) -> App {
+        let mut app = test_app();
+        app.add_asset::<MusicSequence>()
+            .insert_resource(AudioChannel::<MainTrack>::default())
+            .add_system(update_music_sequencer::<MainTrack>);
+        let sequence = app
+            .world
+            .resource_mut::<Assets<MusicSequence>>()
+            .add(sequence);
+        app.world
+            .resource_mut::<AudioChannel<MainTrack>>()
+            .set_music_sequencer(Some(MusicSequencer::new(sequence)));
+        app
+    }
+
+    fn sequencer(app: &mut App) -> &mut MusicSequencer {
+        app.world
+            .resource_mut::<AudioChannel<MainTrack>>()
+            .into_inner()
+            .music_sequencer_mut()
+            .unwrap()
+    }
+
+    fn set_position(app: &mut App, position: f64) {
+        let instance = sequencer(app).current_instance().cloned().unwrap();
+        app.world
+            .resource_mut::<AudioChannel<MainTrack>>()
+            .states
+            .insert(instance, PlaybackState::Playing { position });
+    }
+
+    #[test]
+    fn transitions_from_a_segment_take_precedence() {
+        let sequence = sequence();
+
+        assert_eq!(
+            sequence.transition("explore", "combat").via.as_deref(),
+            Some("alarm")
+        );
+        assert_eq!(
+            sequence.transition("alarm", "combat").timing,
+            TransitionTiming::ExitMarker
+        );
+        assert_eq!(
+            sequence.transition("combat", "explore").timing,
+            TransitionTiming::Immediate
+        );
+    }
+
+    #[test]
+    fn segments_switch_on_the_next_bar_via_the_transition_segment() {
+        let mut app = app(sequence());
+        sequencer(&mut app).request("explore");
+        app.update();
+        assert_eq!(sequencer(&mut app).current_segment(), Some("explore"));
+
+        set_position(&mut app, 1.5);
+        sequencer(&mut app).request("combat");
+        app.update();
+        assert_eq!(sequencer(&mut app).current_segment(), Some("explore"));
+        assert!(sequencer(&mut app).is_transitioning());
+
+        set_position(&mut app, 4.);
+        app.update();
+        assert_eq!(sequencer(&mut app).current_segment(), Some("alarm"));
+
+        // the transition segment ended
+        let alarm = sequencer(&mut app).current_instance().cloned().unwrap();
+        let mut channel = app.world.resource_mut::<AudioChannel<MainTrack>>();
+        channel.commands.write().clear();
+        channel.states.remove(&alarm);
+        app.update();
+        assert_eq!(sequencer(&mut app).current_segment(), Some("combat"));
+        assert!(!sequencer(&mut app).is_transitioning());
+    }
+
+    fn static_source(app: &mut App, seconds: usize) -> Handle<AudioSource> {
+        app.world
+            .resource_mut::<Assets<AudioSource>>()
+            .add(source(vec![Frame::from_mono(1.); seconds * 100]))
+    }
+
+    /// Start ticks of the queued plays and stops
+    fn scheduled_ticks(app: &mut App) -> Vec<(&'static str, Option<u64>)> {
+        let channel = app.world.resource::<AudioChannel<MainTrack>>();
+        let mut commands = channel.commands.write();
+        commands
+            .drain(..)
+            .rev()
+            .filter_map(|command| match command {
+                AudioCommand::Play(args) => Some((
+                    "play",
+                    args.settings.start_time.map(|start_time| start_time.ticks),
+                )),
+                AudioCommand::StopInstance(_, tween) => {
+                    Some(("stop", tween.start_time.map(|start_time| start_time.ticks)))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn transitions_are_scheduled_on_the_clock() {
+        let mut app = test_app();
+        app.add_asset::<MusicSequence>()
+            .insert_non_send_resource(mock_output(default()))
+            .insert_resource(AudioChannel::<MainTrack>::default())
+            .add_system(update_music_sequencer::<MainTrack>);
+        let explore = static_source(&mut app, 10);
+        let alarm = static_source(&mut app, 2);
+        let combat = static_source(&mut app, 10);
+        let sequence = MusicSequence::default()
+            .with_segment(
+                "explore",
+                MusicSegment::new(explore).with_tempo(Tempo::new(60.)),
+            )
+            .with_segment("alarm", MusicSegment::new(alarm).not_looped())
+            .with_segment("combat", MusicSegment::new(combat))
+            .with_transition(
+                MusicTransition::new("combat", TransitionTiming::NextBar)
+                    .from("explore")
+                    .via("alarm"),
+            );
+        let sequence = app
+            .world
+            .resource_mut::<Assets<MusicSequence>>()
+            .add(sequence);
+        app.world
+            .resource_mut::<AudioChannel<MainTrack>>()
+            .set_music_sequencer(Some(MusicSequencer::new(sequence)));
+        let tick = |app: &mut App, seconds: f64| {
+            app.world
+                .non_send_resource_mut::<AudioOutput>()
+                .tick(Duration::from_secs_f64(seconds));
+        };
+
+        sequencer(&mut app).request("explore");
+        app.update();
+        assert_eq!(scheduled_ticks(&mut app), vec![("play", Some(1))]);
+
+        tick(&mut app, 1.5);
+        sequencer(&mut app).request("combat");
+        app.update();
+        // the next bar of explore starts 4 seconds after its start
+        assert_eq!(
+            scheduled_ticks(&mut app),
+            vec![("stop", Some(4001)), ("play", Some(4001))]
+        );
+        assert_eq!(sequencer(&mut app).current_segment(), Some("explore"));
+
+        tick(&mut app, 3.);
+        app.update();
+        // combat starts right after the 2 seconds of the transition segment
+        assert_eq!(scheduled_ticks(&mut app), vec![("play", Some(6001))]);
+        assert_eq!(sequencer(&mut app).current_segment(), Some("alarm"));
+        assert!(sequencer(&mut app).is_transitioning());
+
+        tick(&mut app, 2.);
+        app.update();
+        assert!(scheduled_ticks(&mut app).is_empty());
+        assert_eq!(sequencer(&mut app).current_segment(), Some("combat"));
+        assert!(!sequencer(&mut app).is_transitioning());
+    }
+}
@@ -1,6 +1,9 @@
 use bevy::utils::default;
+use kira::manager::backend::Backend;
+#[cfg(feature = "cpal")]
 use kira::manager::backend::DefaultBackend;
 use kira::manager::{AudioManagerSettings, Capacities};
+use std::time::Duration;
 
 /// This resource is used to configure the audio backend at creation
 ///
@@ -13,10 +16,18 @@ pub struct AudioSettings {
     /// Each action you take, like playing or pausing a sound
     /// queues up one command.
     ///
-    /// Note that configuring a channel will cause one command per sound in the channel!
+    /// Note that changing the playback rate of a channel will cause one command per sound in the channel!
     pub command_capacity: usize,
     /// The maximum number of sounds that can be playing at a time.
     pub sound_capacity: usize,
+    /// The maximum number of clocks that can exist at a time.
+    ///
+    /// Quantized plays use one clock once the first quantized play started.
+    pub clock_capacity: usize,
+    /// What happens when a sound is played while `sound_capacity` sounds are playing
+    pub sound_limit_policy: SoundLimitPolicy,
+    /// The backend to output audio with
+    pub backend: AudioBackend,
 }
 
 impl Default for AudioSettings {
@@ -24,19 +35,96 @@ impl Default for AudioSettings {
         Self {
             command_capacity: 128,
             sound_capacity: 128,
+            clock_capacity: 8,
+            sound_limit_policy: SoundLimitPolicy::DropNew,
+            backend: AudioBackend::Default,
         }
     }
 }
 
-impl From<AudioSettings> for AudioManagerSettings<DefaultBackend> {
-    fn from(settings: AudioSettings) -> Self {
+/// Policy for new sounds when the maximum number of sounds is playing
+///
+/// Sounds are only stopped for new sounds with the same or a higher priority
+/// (see [`PlayAudioCommand::with_priority`](crate::PlayAudioCommand::with_priority)).
+/// Sounds with the lowest priority are stopped first.
+///
+/// Instances that cannot be played report [`PlaybackState::Failed`](crate::PlaybackState::Failed)
+/// and send an [`AudioPlayFailed`](crate::AudioPlayFailed) event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundLimitPolicy {
+    /// Do not play the new sound
+    DropNew,
+    /// Stop the sound that started playing first and play the new sound
+    StealOldest,
+    /// Stop the sound with the lowest volume and play the new sound
+    ///
+    /// The volume includes the volume of the channel and spatial attenuation.
+    StealQuietest,
+}
+
+/// Limit for the number of instances playing in one channel
+///
+/// See [`AudioChannel::set_instance_limit`](crate::AudioChannel::set_instance_limit).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InstanceLimit {
+    /// The maximum number of instances playing in the channel at a time
+    pub max_instances: usize,
+    /// What happens when a sound is played while `max_instances` sounds are playing
+    pub policy: SoundLimitPolicy,
+}
+
+/// Limit for how often the same sound can be played in a channel
+///
+/// See [`AudioChannel::set_cooldown`](crate::AudioChannel::set_cooldown).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlayCooldown {
+    /// The maximum number of times the sound can start within `window`
+    pub max_plays: usize,
+    /// The time span in which plays are counted
+    pub window: Duration,
+}
+
+/// The audio backend processing the mix of all channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioBackend {
+    /// Play audio on the default output device of the system
+    ///
+    /// Requires the feature `cpal` (enabled by default). Without it, no audio is played.
+    Default,
+    /// Process audio without an output device
+    ///
+    /// Nothing is processed unless you call [`AudioOutput::tick`](crate::AudioOutput::tick).
+    /// Sounds still change their state and position when ticked. This is useful for tests and
+    /// dedicated servers without sound card.
+    Mock {
+        /// The number of frames processed per second
+        ///
+        /// Positions of sounds are only as precise as one frame.
+        sample_rate: u32,
+    },
+}
+
+impl AudioSettings {
+    pub(crate) fn manager_settings<B: Backend>(
+        &self,
+        backend_settings: B::Settings,
+    ) -> AudioManagerSettings<B> {
         AudioManagerSettings {
             capacities: Capacities {
-                command_capacity: settings.command_capacity,
-                sound_capacity: settings.sound_capacity,
+                command_capacity: self.command_capacity,
+                sound_capacity: self.sound_capacity,
+                clock_capacity: self.clock_capacity,
                 ..default()
             },
-            ..default()
+            main_track_builder: default(),
+            backend_settings,
         }
     }
 }
+
+#[cfg(feature = "cpal")]
+impl From<AudioSettings> for AudioManagerSettings<DefaultBackend> {
+    fn from(settings: AudioSettings) -> Self {
+        settings.manager_settings(())
+    }
+}
@@ -0,0 +1,258 @@
+use crate::play::PlayAudioSettings;
+use crate::source::{AudioSource, AudioSourceData};
+use bevy::asset::{Assets, Handle};
+use std::ops::RangeInclusive;
+
+/// A group of sounds of which one variant is played at a time
+///
+/// Playing the [`AudioSource`] of a group picks one of its variants. Random volume and playback
+/// rate variations are applied on top of the play command, so that repeated sounds like footsteps
+/// or impacts don't sound identical.
+///
+/// Groups can be loaded from `*.group.ron` files (requires the feature `settings_loader`)
+/// or added to the [`Assets<AudioSource>`](bevy::asset::Assets) in code.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{Audio, AudioSource, AudioSourceGroup, GroupMode};
+///
+/// fn play_footstep(
+///     asset_server: Res<AssetServer>,
+///     mut audio_sources: ResMut<Assets<AudioSource>>,
+///     audio: Res<Audio>,
+/// ) {
+///     let footsteps = AudioSourceGroup {
+///         mode: GroupMode::Shuffle,
+///         volume: 0.8..=1.0,
+///         ..AudioSourceGroup::new(vec![
+///             asset_server.load("footstep_1.ogg"),
+///             asset_server.load("footstep_2.ogg"),
+///         ])
+///     };
+///     audio.play(audio_sources.add(footsteps.into()));
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioSourceGroup {
+    /// The sounds of this group
+    pub variants: Vec<AudioSourceVariant>,
+    /// How the played variant is picked
+    pub mode: GroupMode,
+    /// Range of the random volume multiplier
+    pub volume: RangeInclusive<f64>,
+    /// Range of the random playback rate multiplier
+    ///
+    /// Changing the playback rate changes both the speed and the pitch of the sound.
+    pub playback_rate: RangeInclusive<f64>,
+}
+
+/// A sound of an [`AudioSourceGroup`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioSourceVariant {
+    /// The sound to play
+    ///
+    /// Variants cannot be groups themselves.
+    pub source: Handle<AudioSource>,
+    /// The relative probability to pick this variant
+    ///
+    /// Ignored by [`GroupMode::Sequential`].
+    pub weight: f64,
+}
+
+/// How the variant of an [`AudioSourceGroup`] is picked for a play
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "settings_loader", derive(serde::Deserialize))]
+pub enum GroupMode {
+    /// Pick a random variant every time
+    Random,
+    /// Play every variant once in random order before playing a variant again
+    ///
+    /// The same variant is never played twice in a row.
+    Shuffle,
+    /// Play the variants in the order they are listed in
+    Sequential,
+}
+
+impl Default for GroupMode {
+    fn default() -> Self {
+        GroupMode::Random
+    }
+}
+
+impl AudioSourceGroup {
+    /// Create a group of equally weighted variants without random volume or playback rate
+    pub fn new(sources: Vec<Handle<AudioSource>>) -> Self {
+        AudioSourceGroup {
+            variants: sources
+                .into_iter()
+                .map(|source| AudioSourceVariant { source, weight: 1. })
+                .collect(),
+            mode: GroupMode::default(),
+            volume: 1.0..=1.0,
+            playback_rate: 1.0..=1.0,
+        }
+    }
+
+    pub(crate) fn is_loaded(&self, audio_sources: &Assets<AudioSource>) -> bool {
+        self.variants
+            .iter()
+            .all(|variant| audio_sources.contains(&variant.source))
+    }
+
+    /// Settings to play the given variant with random volume and playback rate
+    pub(crate) fn vary(&self, settings: &PlayAudioSettings, variant: usize) -> PlayAudioSettings {
+        let mut settings = settings.clone();
+        settings.source = self.variants[variant].source.clone();
+        settings.volume = Some(settings.volume.unwrap_or(1.) * random_in(&self.volume));
+        settings.playback_rate =
+            Some(settings.playback_rate.unwrap_or(1.) * random_in(&self.playback_rate));
+        settings
+    }
+}
+
+impl From<AudioSourceGroup> for AudioSourceData {
+    fn from(group: AudioSourceGroup) -> Self {
+        AudioSourceData::Group(group)
+    }
+}
+
+impl From<AudioSourceGroup> for AudioSource {
+    fn from(group: AudioSourceGroup) -> Self {
+        AudioSource::new(group)
+    }
+}
+
+fn random_in(range: &RangeInclusive<f64>) -> f64 {
+    range.start() + fastrand::f64() * (range.end() - range.start())
+}
+
+/// Picks the variants of a group across plays
+#[derive(Default)]
+pub(crate) struct GroupPlayback {
+    /// Number of the next variant in sequential mode
+    next: usize,
+    /// Variants not played yet in the current shuffle round
+    remaining: Vec<usize>,
+    last: Option<usize>,
+}
+
+impl GroupPlayback {
+    /// Pick the variant for the next play
+    ///
+    /// The group must not be empty.
+    pub(crate) fn pick(&mut self, group: &AudioSourceGroup) -> usize {
+        let len = group.variants.len();
+        let variant = match group.mode {
+            GroupMode::Random => pick_weighted(group, (0..len).collect()),
+            GroupMode::Sequential => {
+                let variant = self.next % len;
+                self.next = variant + 1;
+                variant
+            }
+            GroupMode::Shuffle => {
+                self.remaining.retain(|variant| *variant < len);
+                if self.remaining.is_empty() {
+                    self.remaining = (0..len).collect();
+                }
+                let mut candidates = self.remaining.clone();
+                if candidates.len() > 1 {
+                    candidates.retain(|variant| Some(*variant) != self.last);
+                }
+                let variant = pick_weighted(group, candidates);
+                self.remaining.retain(|remaining| *remaining != variant);
+                variant
+            }
+        };
+        self.last = Some(variant);
+        variant
+    }
+}
+
+fn pick_weighted(group: &AudioSourceGroup, candidates: Vec<usize>) -> usize {
+    let weight = |variant: &usize| group.variants[*variant].weight.max(0.);
+    let total: f64 = candidates.iter().map(weight).sum();
+    if total <= 0. {
+        return candidates[fastrand::usize(..candidates.len())];
+    }
+    let mut target = fastrand::f64() * total;
+    for variant in candidates.iter() {
+        target -= weight(variant);
+        if target < 0. {
+            return *variant;
+        }
+    }
+    *candidates.last().unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::asset::HandleId;
+
+    fn group(variants: usize, mode: GroupMode) -> AudioSourceGroup {
+        AudioSourceGroup {
+            mode,
+            ..AudioSourceGroup::new(
+                (0..variants)
+                    .map(|_| Handle::weak(HandleId::random::<AudioSource>()))
+                    .collect(),
+            )
+        }
+    }
+
+    #[test]
+    fn sequential_groups_play_variants_in_order() {
+        let group = group(3, GroupMode::Sequential);
+        let mut playback = GroupPlayback::default();
+
+        let picks: Vec<usize> = (0..5).map(|_| playback.pick(&group)).collect();
+
+        assert_eq!(picks, vec![0, 1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn shuffled_groups_play_every_variant_once_per_round() {
+        let group = group(4, GroupMode::Shuffle);
+        let mut playback = GroupPlayback::default();
+
+        let mut last = None;
+        for _ in 0..10 {
+            let mut round: Vec<usize> = (0..4)
+                .map(|_| {
+                    let variant = playback.pick(&group);
+                    assert_ne!(Some(variant), last);
+                    last = Some(variant);
+                    variant
+                })
+                .collect();
+            round.sort_unstable();
+            assert_eq!(round, vec![0, 1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn variants_without_weight_are_not_picked() {
+        let mut group = group(3, GroupMode::Random);
+        group.variants[0].weight = 0.;
+        group.variants[2].weight = 0.;
+        let mut playback = GroupPlayback::default();
+
+        assert!((0..20).all(|_| playback.pick(&group) == 1));
+    }
+
+    #[test]
+    fn variations_are_applied_on_top_of_play_settings() {
+        let mut group = group(2, GroupMode::Random);
+        group.volume = 0.5..=0.5;
+        group.playback_rate = 0.8..=1.2;
+        let mut settings = PlayAudioSettings::new(Handle::weak(HandleId::default::<AudioSource>()));
+        settings.volume = Some(0.5);
+
+        let varied = group.vary(&settings, 1);
+
+        assert_eq!(varied.source, group.variants[1].source);
+        assert_eq!(varied.volume, Some(0.25));
+        let playback_rate = varied.playback_rate.unwrap();
+        assert!((0.8..=1.2).contains(&playback_rate));
+    }
+}
@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+use bevy::asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset};
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+
+use crate::source::group::{AudioSourceGroup, AudioSourceVariant, GroupMode};
+use crate::AudioSource;
+
+#[derive(Default)]
+pub struct GroupLoader;
+
+/// Settings of a sound group
+///
+/// This is used when loading from a *.group.ron file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GroupSettings {
+    /// The sounds of the group
+    variants: Vec<VariantSettings>,
+    /// How the played variant is picked
+    #[serde(default)]
+    mode: GroupMode,
+    /// Range of the random volume multiplier
+    #[serde(default = "default_range")]
+    volume: (f64, f64),
+    /// Range of the random playback rate multiplier
+    #[serde(default = "default_range")]
+    playback_rate: (f64, f64),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VariantSettings {
+    /// Location of the sound file
+    ///
+    /// This can also be a settings file of a sound.
+    file: PathBuf,
+    /// The relative probability to pick this variant
+    #[serde(default = "default_weight")]
+    weight: f64,
+}
+
+fn default_range() -> (f64, f64) {
+    (1.0, 1.0)
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+impl AssetLoader for GroupLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let settings: GroupSettings = ron::de::from_bytes(bytes)?;
+            let mut dependencies = vec![];
+            let variants = settings
+                .variants
+                .into_iter()
+                .map(|variant| {
+                    let path = AssetPath::new(variant.file, None);
+                    let source = load_context.get_handle(path.clone());
+                    dependencies.push(path);
+                    AudioSourceVariant {
+                        source,
+                        weight: variant.weight,
+                    }
+                })
+                .collect();
+            let group = AudioSourceGroup {
+                variants,
+                mode: settings.mode,
+                volume: settings.volume.0..=settings.volume.1,
+                playback_rate: settings.playback_rate.0..=settings.playback_rate.1,
+            };
+
+            load_context.set_default_asset(
+                LoadedAsset::new(AudioSource::from(group)).with_dependencies(dependencies),
+            );
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["group.ron"]
+    }
+}
@@ -1,21 +1,176 @@
 #[cfg(feature = "flac")]
 pub mod flac_loader;
+pub mod group;
+#[cfg(feature = "settings_loader")]
+pub mod group_loader;
 #[cfg(feature = "mp3")]
 pub mod mp3_loader;
 #[cfg(feature = "ogg")]
 pub mod ogg_loader;
 #[cfg(feature = "settings_loader")]
+pub mod sequence_loader;
+#[cfg(feature = "settings_loader")]
 pub mod settings_loader;
+#[cfg(all(
+    any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+    not(target_arch = "wasm32")
+))]
+pub mod streaming_loader;
 #[cfg(feature = "wav")]
 pub mod wav_loader;
 
+use crate::spatial::Attenuation;
+use crate::tempo::Tempo;
 use bevy::reflect::TypeUuid;
-use kira::sound::static_sound::StaticSoundData;
+use group::AudioSourceGroup;
+use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
+#[cfg(all(
+    any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+    not(target_arch = "wasm32")
+))]
+use kira::sound::streaming::StreamingSoundSettings;
+#[cfg(all(
+    any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+    not(target_arch = "wasm32")
+))]
+use std::sync::Arc;
 
 /// A source of audio data
+///
+/// Create sources in code with [`AudioSource::new`]. More fields might be added in the future.
 #[derive(Clone, TypeUuid)]
 #[uuid = "6a9fc4ca-b5b5-94d6-613c-522e2d9fe86d"]
+#[non_exhaustive]
 pub struct AudioSource {
     /// The Kira sound making up this `AudioSource`
-    pub sound: StaticSoundData,
+    pub sound: AudioSourceData,
+    /// Attenuation of this sound when played by an [`AudioEmitter`](crate::AudioEmitter)
+    ///
+    /// Can be set in the settings file of a sound (requires the feature `settings_loader`).
+    pub attenuation: Option<Attenuation>,
+    /// Position in seconds at which a looping playback jumps back to its loop start
+    ///
+    /// By default, sounds loop at their end. Loop ends are only supported for static sounds that
+    /// do not play in reverse. Can be set in the settings file of a sound (requires the feature
+    /// `settings_loader`).
+    pub loop_end: Option<f64>,
+    /// Musical tempo of this sound, used to send beat and bar events while it plays
+    ///
+    /// Can be set in the settings file of a sound (requires the feature `settings_loader`).
+    pub tempo: Option<Tempo>,
+}
+
+/// The audio data of an [`AudioSource`]
+///
+/// Files are loaded as static sounds by default. Use the extension `.stream.<format>`
+/// (e.g. `music.stream.ogg`) or `stream: true` in a settings file to load a file as streaming
+/// sound. Files with the extension `.group.ron` are loaded as [`AudioSourceGroup`].
+#[derive(Clone)]
+pub enum AudioSourceData {
+    /// Sound that is fully decoded into memory when loading the asset
+    ///
+    /// Best for short sound effects.
+    Static(StaticSoundData),
+    /// Sound that is decoded while it is playing
+    ///
+    /// Only the encoded file is kept in memory. Best for long music tracks.
+    /// Streaming sounds cannot be played in reverse or with an intro.
+    #[cfg(all(
+        any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+        not(target_arch = "wasm32")
+    ))]
+    Streaming {
+        /// The encoded audio file
+        bytes: Arc<[u8]>,
+        /// Kira settings for playing the sound
+        settings: StreamingSoundSettings,
+    },
+    /// Group of sounds of which one variant is played at a time
+    Group(AudioSourceGroup),
+}
+
+impl AudioSource {
+    /// Create a source playing the given sound without attenuation, loop end, or tempo
+    pub fn new(sound: impl Into<AudioSourceData>) -> Self {
+        AudioSource {
+            sound: sound.into(),
+            attenuation: None,
+            loop_end: None,
+            tempo: None,
+        }
+    }
+}
+
+impl From<StaticSoundData> for AudioSourceData {
+    fn from(sound: StaticSoundData) -> Self {
+        AudioSourceData::Static(sound)
+    }
+}
+
+impl AudioSourceData {
+    /// The settings of this sound as settings of a static sound
+    pub(crate) fn settings(&self) -> StaticSoundSettings {
+        match self {
+            AudioSourceData::Static(sound) => sound.settings,
+            #[cfg(all(
+                any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+                not(target_arch = "wasm32")
+            ))]
+            AudioSourceData::Streaming { settings, .. } => {
+                let mut static_settings = StaticSoundSettings::new();
+                static_settings.start_time = settings.start_time;
+                static_settings.start_position = settings.start_position;
+                static_settings.volume = settings.volume;
+                static_settings.playback_rate = settings.playback_rate;
+                static_settings.panning = settings.panning;
+                static_settings.loop_behavior = settings.loop_behavior;
+                static_settings.track = settings.track;
+                static_settings.fade_in_tween = settings.fade_in_tween;
+                static_settings
+            }
+            AudioSourceData::Group(_) => StaticSoundSettings::new(),
+        }
+    }
+}
+
+#[cfg(all(
+    any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+    not(target_arch = "wasm32")
+))]
+pub(crate) fn streaming_settings(settings: &StaticSoundSettings) -> StreamingSoundSettings {
+    let mut streaming_settings = StreamingSoundSettings::new();
+    streaming_settings.start_time = settings.start_time;
+    streaming_settings.start_position = settings.start_position;
+    streaming_settings.volume = settings.volume;
+    streaming_settings.playback_rate = settings.playback_rate;
+    streaming_settings.panning = settings.panning;
+    streaming_settings.loop_behavior = settings.loop_behavior;
+    streaming_settings.track = settings.track;
+    streaming_settings.fade_in_tween = settings.fade_in_tween;
+    streaming_settings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(all(
+        any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+        not(target_arch = "wasm32")
+    ))]
+    #[test]
+    fn streaming_settings_keep_sound_settings() {
+        let settings = StaticSoundSettings::new()
+            .volume(0.5)
+            .playback_rate(2.)
+            .panning(0.2)
+            .start_position(3.)
+            .loop_behavior(kira::LoopBehavior { start_position: 1. });
+        let sound = AudioSourceData::Streaming {
+            bytes: Arc::from(vec![]),
+            settings: streaming_settings(&settings),
+        };
+
+        assert_eq!(sound.settings(), settings);
+    }
 }
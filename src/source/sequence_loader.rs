@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset};
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+
+use crate::sequence::{MusicSegment, MusicSequence, MusicTransition, TransitionTiming};
+use crate::Tempo;
+
+#[derive(Default)]
+pub struct SequenceLoader;
+
+/// Segments and transitions of a music sequence
+///
+/// This is used when loading from a *.sequence.ron file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SequenceSettings {
+    /// The segments by name
+    segments: HashMap<String, SegmentSettings>,
+    /// Rules for switching from one segment to another
+    #[serde(default)]
+    transitions: Vec<TransitionSettings>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SegmentSettings {
+    /// Location of the sound file
+    ///
+    /// This can also be a settings file of a sound.
+    file: PathBuf,
+    /// Whether the segment loops until the next transition
+    #[serde(default = "default_looped")]
+    looped: bool,
+    /// The tempo used for transitions on the next beat or bar
+    #[serde(default)]
+    tempo: Option<Tempo>,
+    /// Positions in seconds at which transitions can leave the segment
+    #[serde(default)]
+    exit_markers: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransitionSettings {
+    /// The segment to switch from; all segments if not set
+    #[serde(default)]
+    from: Option<String>,
+    /// The segment to switch to
+    to: String,
+    /// When to leave the current segment
+    #[serde(default)]
+    timing: TransitionTiming,
+    /// A segment played once between the current and the target segment
+    #[serde(default)]
+    via: Option<String>,
+    /// Duration of the crossfade in milliseconds
+    #[serde(default)]
+    fade: Option<u64>,
+}
+
+fn default_looped() -> bool {
+    true
+}
+
+impl AssetLoader for SequenceLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let settings: SequenceSettings = ron::de::from_bytes(bytes)?;
+            for transition in settings.transitions.iter() {
+                let segments = transition
+                    .from
+                    .iter()
+                    .chain(transition.via.iter())
+                    .chain(std::iter::once(&transition.to));
+                for segment in segments {
+                    if !settings.segments.contains_key(segment) {
+                        return Err(anyhow::anyhow!(
+                            "Transition uses the unknown segment '{}'",
+                            segment
+                        ));
+                    }
+                }
+            }
+            let mut dependencies = vec![];
+            let segments = settings
+                .segments
+                .into_iter()
+                .map(|(name, segment)| {
+                    let path = AssetPath::new(segment.file, None);
+                    let source = load_context.get_handle(path.clone());
+                    dependencies.push(path);
+                    let segment = MusicSegment {
+                        source,
+                        looped: segment.looped,
+                        tempo: segment.tempo,
+                        exit_markers: segment.exit_markers,
+                    };
+                    (name, segment)
+                })
+                .collect();
+            let transitions = settings
+                .transitions
+                .into_iter()
+                .map(|transition| MusicTransition {
+                    from: transition.from,
+                    to: transition.to,
+                    timing: transition.timing,
+                    via: transition.via,
+                    fade: transition.fade.map(Duration::from_millis),
+                })
+                .collect();
+
+            load_context.set_default_asset(
+                LoadedAsset::new(MusicSequence {
+                    segments,
+                    transitions,
+                })
+                .with_dependencies(dependencies),
+            );
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sequence.ron"]
+    }
+}
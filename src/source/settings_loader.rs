@@ -8,7 +8,13 @@ use kira::tween::Tween;
 use kira::{LoopBehavior, PlaybackRate, Volume};
 use serde::Deserialize;
 
-use crate::AudioSource;
+use crate::source::AudioSourceData;
+#[cfg(all(
+    any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+    not(target_arch = "wasm32")
+))]
+use crate::source::{streaming_loader, streaming_settings};
+use crate::{Attenuation, AudioSource, Tempo};
 
 #[derive(Default)]
 pub struct SettingsLoader;
@@ -28,7 +34,7 @@ struct SoundSettings {
     pub start_position: f64,
     /// Amplitude multiplier
     ///
-    /// If the channel you play the sound is configured, it will overwrite the volume here.
+    /// The volume of the channel you play the sound in is applied on top.
     #[serde(default = "default_one")]
     pub volume: f64,
     /// The playback rate of the sound.
@@ -36,13 +42,13 @@ struct SoundSettings {
     /// Changing the playback rate will change both the speed
     /// and the pitch of the sound.
     ///
-    /// If the channel you play the sound is configured, it will overwrite the volume here.
+    /// The playback rate of the channel you play the sound in is applied on top.
     #[serde(default = "default_one")]
     pub playback_rate: f64,
     /// The panning of the sound, where 0 is hard left
     /// and 1 is hard right.
     ///
-    /// If the channel you play the sound is configured, it will overwrite the volume here.
+    /// The panning of the channel you play the sound in is applied on top.
     #[serde(default = "default_panning")]
     pub panning: f64,
     /// Whether the sound should play in reverse.
@@ -57,11 +63,32 @@ struct SoundSettings {
     /// has been reached.
     #[serde(default)]
     pub loop_behavior: Option<f64>,
+    /// The end of the loop region in seconds
+    ///
+    /// If you set a value here, a looping sound jumps back to its loop start at this position
+    /// instead of at its end. Only has an effect together with `loop_behavior`.
+    #[serde(default)]
+    pub loop_end: Option<f64>,
     /// An optional linear fade-in from silence.
     ///
     /// The [`u64`] value is the duration of the tween in milliseconds.
     #[serde(default)]
     pub fade_in_tween: Option<u64>,
+    /// Attenuation of the sound when played as positional audio
+    ///
+    /// Attenuation configured on an emitter takes precedence.
+    #[serde(default)]
+    pub attenuation: Option<Attenuation>,
+    /// Musical tempo of the sound to send beat and bar events while it plays
+    #[serde(default)]
+    pub tempo: Option<Tempo>,
+    /// Whether the sound should be streamed
+    ///
+    /// Streamed sounds are decoded while they are playing instead of when they are loaded.
+    /// This saves memory and loading time for long sounds like music.
+    /// Streamed sounds cannot be played in reverse and are not supported in wasm.
+    #[serde(default)]
+    pub stream: bool,
 }
 
 fn default_one() -> f64 {
@@ -93,6 +120,30 @@ impl From<SoundSettings> for StaticSoundSettings {
     }
 }
 
+#[cfg(all(
+    any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+    not(target_arch = "wasm32")
+))]
+fn streaming_sound(bytes: &[u8], settings: StaticSoundSettings) -> anyhow::Result<AudioSourceData> {
+    if settings.reverse {
+        bevy::log::warn!("Streamed sounds cannot be played in reverse");
+    }
+    streaming_loader::streaming_sound(bytes, streaming_settings(&settings))
+}
+
+#[cfg(not(all(
+    any(feature = "ogg", feature = "mp3", feature = "flac", feature = "wav"),
+    not(target_arch = "wasm32")
+)))]
+fn streaming_sound(
+    _bytes: &[u8],
+    _settings: StaticSoundSettings,
+) -> anyhow::Result<AudioSourceData> {
+    Err(anyhow::anyhow!(
+        "Streamed sounds are not supported in wasm and require one of the features ogg, mp3, flac or wav"
+    ))
+}
+
 impl AssetLoader for SettingsLoader {
     fn load<'a>(
         &'a self,
@@ -100,13 +151,25 @@ impl AssetLoader for SettingsLoader {
         load_context: &'a mut LoadContext,
     ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
         Box::pin(async move {
-            let sound_settings: SoundSettings = ron::de::from_bytes(bytes)?;
+            let mut sound_settings: SoundSettings = ron::de::from_bytes(bytes)?;
+            let attenuation = sound_settings.attenuation.take();
+            let loop_end = sound_settings.loop_end;
+            let tempo = sound_settings.tempo;
             let sound_bytes = load_context.read_asset_bytes(&sound_settings.file).await?;
 
-            let sound =
-                StaticSoundData::from_cursor(Cursor::new(sound_bytes), sound_settings.into())?;
+            let stream = sound_settings.stream;
+            let settings: StaticSoundSettings = sound_settings.into();
+            let sound = if stream {
+                streaming_sound(&sound_bytes, settings)?
+            } else {
+                StaticSoundData::from_cursor(Cursor::new(sound_bytes), settings)?.into()
+            };
 
-            load_context.set_default_asset(LoadedAsset::new(AudioSource { sound }));
+            let mut source = AudioSource::new(sound);
+            source.attenuation = attenuation;
+            source.loop_end = loop_end;
+            source.tempo = tempo;
+            load_context.set_default_asset(LoadedAsset::new(source));
 
             Ok(())
         })
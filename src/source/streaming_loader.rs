@@ -0,0 +1,58 @@
+use anyhow::Result;
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::utils::BoxedFuture;
+use kira::sound::streaming::{StreamingSoundData, StreamingSoundSettings};
+use std::io::Cursor;
+use std::sync::Arc;
+
+use crate::source::{AudioSource, AudioSourceData};
+
+/// Loader for audio files that should be streamed
+///
+/// Files with the extension `.stream.<format>` (e.g. `music.stream.ogg`) are decoded while they
+/// are playing instead of when they are loaded.
+#[derive(Default)]
+pub struct StreamingLoader;
+
+impl AssetLoader for StreamingLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let sound = streaming_sound(bytes, StreamingSoundSettings::default())?;
+            load_context.set_default_asset(LoadedAsset::new(AudioSource::new(sound)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[
+            #[cfg(feature = "mp3")]
+            "stream.mp3",
+            #[cfg(feature = "wav")]
+            "stream.wav",
+            #[cfg(feature = "flac")]
+            "stream.flac",
+            #[cfg(feature = "ogg")]
+            "stream.ogg",
+            #[cfg(feature = "ogg")]
+            "stream.oga",
+            #[cfg(feature = "ogg")]
+            "stream.spx",
+        ]
+    }
+}
+
+/// Create the data of a streaming sound
+///
+/// The file is opened once to make sure that it can be decoded.
+pub(crate) fn streaming_sound(
+    bytes: &[u8],
+    settings: StreamingSoundSettings,
+) -> Result<AudioSourceData> {
+    let bytes: Arc<[u8]> = Arc::from(bytes);
+    StreamingSoundData::from_cursor(Cursor::new(bytes.clone()), settings)?;
+    Ok(AudioSourceData::Streaming { bytes, settings })
+}
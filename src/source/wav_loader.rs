@@ -24,7 +24,7 @@ impl AssetLoader for WavLoader {
                 Cursor::new(sound_bytes),
                 StaticSoundSettings::default(),
             )?;
-            load_context.set_default_asset(LoadedAsset::new(AudioSource { sound }));
+            load_context.set_default_asset(LoadedAsset::new(AudioSource::new(sound)));
             Ok(())
         })
     }
@@ -0,0 +1,269 @@
+use crate::audio::InstanceHandle;
+use crate::audio_output::AudioOutput;
+use bevy::prelude::{Component, GlobalTransform, NonSendMut, Query, Res, Vec3, With};
+
+/// Component for entities emitting positional audio
+///
+/// Add the handles of all playback instances that should be heard from the position of this
+/// entity. Their volume and panning will be updated every frame relative to the
+/// [`AudioReceiver`].
+///
+/// Volume changes of the instances are applied on top of the spatial attenuation. Changes to the
+/// panning of the instances are overwritten.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{Audio, AudioEmitter};
+///
+/// fn spawn_engine(mut commands: Commands, asset_server: Res<AssetServer>, audio: Res<Audio>) {
+///     let engine_sound = audio.play(asset_server.load("engine.ogg")).looped().handle();
+///     commands
+///         .spawn_bundle(TransformBundle::from_transform(Transform::from_xyz(10., 0., 0.)))
+///         .insert(AudioEmitter {
+///             instances: vec![engine_sound],
+///         });
+/// }
+/// ```
+#[derive(Component, Default, Debug, Clone)]
+pub struct AudioEmitter {
+    /// Playback instances emitted from this entity
+    pub instances: Vec<InstanceHandle>,
+}
+
+/// Component for the entity that hears positional audio
+///
+/// This is usually the camera or the player. There should only be one receiver at a time.
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct AudioReceiver;
+
+/// Configuration of positional audio
+///
+/// Insert this resource to change the defaults.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpatialAudio {
+    /// Attenuation of emitters without their own configuration
+    ///
+    /// See [`Attenuation`] for how to configure single emitters or sounds.
+    pub default_attenuation: Attenuation,
+}
+
+/// How the volume of positional audio falls off with distance
+///
+/// Add this as a component to an entity with an [`AudioEmitter`] to configure its attenuation.
+/// Sounds can also define their attenuation in their settings file (requires the feature
+/// `settings_loader`). The attenuation of an emitter takes precedence over the one of a sound.
+/// If neither is set, [`SpatialAudio::default_attenuation`] is used.
+///
+/// Distances are clamped between `min_distance` and `max_distance`.
+#[derive(Component, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "settings_loader", derive(serde::Deserialize))]
+#[cfg_attr(feature = "settings_loader", serde(default, deny_unknown_fields))]
+pub struct Attenuation {
+    /// The curve of the volume falloff
+    pub model: DistanceModel,
+    /// Up to this distance, sounds play at full volume
+    pub min_distance: f32,
+    /// Sounds do not get quieter beyond this distance
+    ///
+    /// With [`DistanceModel::Linear`], sounds cannot be heard beyond this distance.
+    pub max_distance: f32,
+    /// How fast the volume falls off
+    ///
+    /// Ignored by [`DistanceModel::Curve`].
+    pub rolloff: f32,
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Attenuation {
+            model: DistanceModel::Linear,
+            min_distance: 1.,
+            max_distance: 100.,
+            rolloff: 1.,
+        }
+    }
+}
+
+/// Volume falloff curves for positional audio
+///
+/// `d` is the distance clamped between `min_distance` and `max_distance` of the [`Attenuation`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "settings_loader", derive(serde::Deserialize))]
+pub enum DistanceModel {
+    /// `1 - rolloff * (d - min_distance) / (max_distance - min_distance)`
+    Linear,
+    /// `min_distance / (min_distance + rolloff * (d - min_distance))`
+    Inverse,
+    /// `(d / min_distance) ^ -rolloff`
+    Exponential,
+    /// Custom curve through points of `(distance, volume)`
+    ///
+    /// The distance of the points goes from 0 at `min_distance` to 1 at `max_distance`.
+    /// The volume is interpolated linearly between the points.
+    Curve(Vec<(f32, f32)>),
+}
+
+impl Attenuation {
+    /// Volume multiplier for a sound at the given distance from the receiver
+    pub fn volume(&self, distance: f32) -> f32 {
+        let min_distance = self.min_distance.max(f32::EPSILON);
+        let max_distance = self.max_distance.max(min_distance);
+        let distance = distance.clamp(min_distance, max_distance);
+        let range = max_distance - min_distance;
+        let volume = match &self.model {
+            DistanceModel::Linear => {
+                if range > 0. {
+                    1. - self.rolloff * (distance - min_distance) / range
+                } else {
+                    1.
+                }
+            }
+            DistanceModel::Inverse => {
+                min_distance / (min_distance + self.rolloff * (distance - min_distance))
+            }
+            DistanceModel::Exponential => (distance / min_distance).powf(-self.rolloff),
+            DistanceModel::Curve(points) => {
+                let progress = if range > 0. {
+                    (distance - min_distance) / range
+                } else {
+                    0.
+                };
+                curve_value(points, progress)
+            }
+        };
+        volume.clamp(0., 1.)
+    }
+}
+
+fn curve_value(points: &[(f32, f32)], progress: f32) -> f32 {
+    let next = points
+        .iter()
+        .position(|(distance, _)| *distance >= progress);
+    match next {
+        None => points.last().map_or(1., |(_, volume)| *volume),
+        Some(0) => points[0].1,
+        Some(index) => {
+            let (start_distance, start_volume) = points[index - 1];
+            let (end_distance, end_volume) = points[index];
+            let factor = (progress - start_distance) / (end_distance - start_distance);
+            start_volume + (end_volume - start_volume) * factor
+        }
+    }
+}
+
+/// Panning of a sound emitted from the given direction relative to the receiver
+///
+/// Sounds to the right of the receiver are panned to the right.
+pub(crate) fn panning(receiver: &GlobalTransform, sound_path: Vec3) -> f32 {
+    if sound_path.length_squared() < f32::EPSILON {
+        return 0.5;
+    }
+    let right_ear_angle = receiver.right().angle_between(sound_path);
+    (right_ear_angle.cos() + 1.) / 2.
+}
+
+pub(crate) fn run_spatial_audio(
+    mut audio_output: NonSendMut<AudioOutput>,
+    spatial_audio: Res<SpatialAudio>,
+    receivers: Query<&GlobalTransform, With<AudioReceiver>>,
+    emitters: Query<(&GlobalTransform, &AudioEmitter, Option<&Attenuation>)>,
+) {
+    let receiver = if let Ok(receiver) = receivers.get_single() {
+        receiver
+    } else {
+        return;
+    };
+    for (emitter_transform, emitter, attenuation) in emitters.iter() {
+        let sound_path = emitter_transform.translation() - receiver.translation();
+        let distance = sound_path.length();
+        let panning = panning(receiver, sound_path);
+        for instance_handle in emitter.instances.iter() {
+            audio_output.set_spatial(instance_handle, panning as f64, |source_attenuation| {
+                attenuation
+                    .or(source_attenuation)
+                    .unwrap_or(&spatial_audio.default_attenuation)
+                    .volume(distance) as f64
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn attenuation(model: DistanceModel) -> Attenuation {
+        Attenuation {
+            model,
+            min_distance: 1.,
+            max_distance: 11.,
+            rolloff: 1.,
+        }
+    }
+
+    #[test]
+    fn linear_attenuation() {
+        let attenuation = attenuation(DistanceModel::Linear);
+
+        assert_eq!(attenuation.volume(0.), 1.);
+        assert_eq!(attenuation.volume(6.), 0.5);
+        assert_eq!(attenuation.volume(11.), 0.);
+        assert_eq!(attenuation.volume(20.), 0.);
+    }
+
+    #[test]
+    fn inverse_attenuation() {
+        let attenuation = attenuation(DistanceModel::Inverse);
+
+        assert_eq!(attenuation.volume(1.), 1.);
+        assert_eq!(attenuation.volume(4.), 0.25);
+        assert_eq!(attenuation.volume(20.), attenuation.volume(11.));
+    }
+
+    #[test]
+    fn exponential_attenuation() {
+        let attenuation = Attenuation {
+            rolloff: 2.,
+            ..attenuation(DistanceModel::Exponential)
+        };
+
+        assert_eq!(attenuation.volume(0.5), 1.);
+        assert_eq!(attenuation.volume(2.), 0.25);
+    }
+
+    #[test]
+    fn curve_attenuation() {
+        let attenuation = attenuation(DistanceModel::Curve(vec![(0., 1.), (0.5, 0.8), (1., 0.)]));
+
+        assert_eq!(attenuation.volume(1.), 1.);
+        assert!((attenuation.volume(3.5) - 0.9).abs() < 1e-6);
+        assert!((attenuation.volume(8.5) - 0.4).abs() < 1e-6);
+        assert_eq!(attenuation.volume(11.), 0.);
+    }
+
+    #[cfg(feature = "settings_loader")]
+    #[test]
+    fn attenuation_is_deserialized_with_defaults() {
+        let attenuation: Attenuation =
+            ron::de::from_str("(model: Curve([(0., 1.), (1., 0.)]), max_distance: 50.)").unwrap();
+
+        assert_eq!(
+            attenuation,
+            Attenuation {
+                model: DistanceModel::Curve(vec![(0., 1.), (1., 0.)]),
+                max_distance: 50.,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn sounds_are_panned_by_direction() {
+        let receiver = GlobalTransform::identity();
+
+        assert!((panning(&receiver, Vec3::X) - 1.).abs() < 1e-6);
+        assert!(panning(&receiver, -Vec3::X).abs() < 1e-6);
+        assert!((panning(&receiver, Vec3::Z) - 0.5).abs() < 1e-6);
+        assert_eq!(panning(&receiver, Vec3::ZERO), 0.5);
+    }
+}
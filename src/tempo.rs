@@ -0,0 +1,140 @@
+use std::ops::Range;
+
+/// Musical tempo of an [`AudioSource`](crate::AudioSource)
+///
+/// While an instance of a sound with a tempo plays, [`AudioBeat`](crate::AudioBeat) and
+/// [`AudioBar`](crate::AudioBar) events are sent on every beat and bar. The tempo can be set
+/// in the settings file of a sound (requires the feature `settings_loader`) or on the
+/// loaded asset.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{AudioBar, AudioSource, Tempo};
+/// struct Music(Handle<AudioSource>);
+///
+/// fn set_tempo(music: Res<Music>, mut audio_sources: ResMut<Assets<AudioSource>>) {
+///     if let Some(source) = audio_sources.get_mut(&music.0) {
+///         // 3/4 time at 90 beats per minute, with the first beat after 0.5 seconds
+///         source.tempo = Some(Tempo::new(90.).with_beats_per_bar(3).with_offset(0.5));
+///     }
+/// }
+///
+/// fn pulse_lights(mut bars: EventReader<AudioBar>) {
+///     for bar in bars.iter() {
+///         info!("Bar {} started", bar.bar);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "settings_loader", derive(serde::Deserialize))]
+#[cfg_attr(feature = "settings_loader", serde(default, deny_unknown_fields))]
+pub struct Tempo {
+    /// Beats per minute
+    pub bpm: f64,
+    /// Number of beats in a bar
+    ///
+    /// This is the upper number of the time signature. The beats counted by `bpm` are the note
+    /// values of the lower number (e.g. eighth notes for 6/8).
+    pub beats_per_bar: u32,
+    /// Position of the first beat in the sound in seconds
+    pub offset: f64,
+}
+
+impl Default for Tempo {
+    fn default() -> Self {
+        Tempo::new(120.)
+    }
+}
+
+impl Tempo {
+    /// Create a tempo in 4/4 time with the first beat at the start of the sound
+    pub fn new(bpm: f64) -> Self {
+        Tempo {
+            bpm,
+            beats_per_bar: 4,
+            offset: 0.,
+        }
+    }
+
+    /// Set the number of beats in a bar
+    pub fn with_beats_per_bar(mut self, beats_per_bar: u32) -> Self {
+        self.beats_per_bar = beats_per_bar;
+        self
+    }
+
+    /// Set the position of the first beat in seconds
+    pub fn with_offset(mut self, offset: f64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Duration of a beat in seconds
+    pub fn beat_duration(&self) -> f64 {
+        60. / self.bpm
+    }
+
+    /// Duration of a bar in seconds
+    pub fn bar_duration(&self) -> f64 {
+        self.beat_duration() * f64::from(self.beats_per_bar.max(1))
+    }
+
+    /// The number of beats since the first beat at the given position in seconds
+    ///
+    /// The fractional part is the progress of the current beat. Positions before
+    /// the first beat are negative.
+    pub fn beat_at(&self, position: f64) -> f64 {
+        (position - self.offset) / self.beat_duration()
+    }
+
+    /// The number of bars since the first beat at the given position in seconds
+    ///
+    /// The fractional part is the progress of the current bar. Positions before
+    /// the first beat are negative.
+    pub fn bar_at(&self, position: f64) -> f64 {
+        (position - self.offset) / self.bar_duration()
+    }
+
+    /// Beats reached when the playback position moved from `previous` to `position`
+    ///
+    /// When an instance starts or its position jumps back (e.g. when looping), the beat at
+    /// the new position is reached if it differs from the previous one.
+    pub(crate) fn reached_beats(&self, previous: Option<f64>, position: f64) -> Range<u64> {
+        if self.bpm.is_nan() || self.bpm <= 0. {
+            return 0..0;
+        }
+        let current = self.beat_at(position).floor();
+        let first = match previous.map(|previous| self.beat_at(previous).floor()) {
+            Some(previous) if previous == current => return 0..0,
+            Some(previous) if previous < current => previous + 1.,
+            _ => current,
+        };
+        if current < 0. {
+            return 0..0;
+        }
+        first.max(0.) as u64..current as u64 + 1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn beats_are_reached_once() {
+        let tempo = Tempo::new(120.).with_offset(0.1);
+
+        assert_eq!(tempo.reached_beats(None, 0.05), 0..0);
+        assert_eq!(tempo.reached_beats(Some(0.05), 0.12), 0..1);
+        assert_eq!(tempo.reached_beats(Some(0.12), 0.3), 0..0);
+        assert_eq!(tempo.reached_beats(Some(0.3), 1.7), 1..4);
+    }
+
+    #[test]
+    fn beat_at_new_position_is_reached_after_jumping_back() {
+        let tempo = Tempo::new(60.);
+
+        assert_eq!(tempo.reached_beats(None, 2.5), 2..3);
+        assert_eq!(tempo.reached_beats(Some(3.9), 1.2), 1..2);
+        assert_eq!(tempo.reached_beats(Some(1.9), 1.2), 0..0);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::audio::AudioCommand;
+use kira::clock::ClockTime;
+use kira::tween::{Easing, Tween};
+use kira::StartTime;
+use parking_lot::RwLock;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// A tween to smoothly transition from one value to another
+///
+/// Tweens can be used with all [`AudioChannel`](crate::AudioChannel) commands returning a
+/// [`TweenCommand`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioTween {
+    /// Time to wait before starting the transition
+    ///
+    /// The delay is counted down once per frame, so it is only frame accurate and can add up
+    /// to one frame of latency. Use [`with_start_time`](Self::with_start_time) to start sample
+    /// accurate.
+    pub start_delay: Duration,
+    /// Duration of the transition
+    pub duration: Duration,
+    /// The curve of the transition
+    pub easing: AudioEasing,
+    /// Clock time to start the transition at
+    ///
+    /// The transition starts sample accurate when the clock reaches this time, after the
+    /// `start_delay` passed. See [`AudioClock`](crate::AudioClock).
+    pub start_time: Option<ClockTime>,
+}
+
+impl AudioTween {
+    /// Create a new tween with the given duration and easing
+    pub fn new(duration: Duration, easing: AudioEasing) -> Self {
+        AudioTween {
+            start_delay: Duration::ZERO,
+            duration,
+            easing,
+            start_time: None,
+        }
+    }
+
+    /// Create a new linear tween with the given duration
+    pub fn linear(duration: Duration) -> Self {
+        AudioTween::new(duration, AudioEasing::Linear)
+    }
+
+    /// Wait for the given duration before starting the transition
+    ///
+    /// The delay is counted down once per frame, so the transition starts in the first frame
+    /// after the delay passed. Use [`with_start_time`](Self::with_start_time) to start sample
+    /// accurate.
+    pub fn with_delay(mut self, start_delay: Duration) -> Self {
+        self.start_delay = start_delay;
+        self
+    }
+
+    /// Start the transition when the clock reaches the given time
+    pub fn with_start_time(mut self, start_time: ClockTime) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+}
+
+impl Default for AudioTween {
+    /// A very short linear transition without delay
+    ///
+    /// Changes are never fully instant to prevent audible clicks.
+    fn default() -> Self {
+        AudioTween::linear(Duration::from_millis(10))
+    }
+}
+
+impl From<&AudioTween> for Tween {
+    fn from(tween: &AudioTween) -> Self {
+        Tween {
+            duration: tween.duration,
+            easing: tween.easing.into(),
+            start_time: tween
+                .start_time
+                .map_or(StartTime::Immediate, StartTime::ClockTime),
+        }
+    }
+}
+
+/// Easing curves for [`AudioTween`]s
+///
+/// `x` is the progress of the tween between 0 and 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AudioEasing {
+    /// `f(x) = x`
+    Linear,
+    /// `f(x) = x ^ power`
+    InPowi(i32),
+    /// `f(x) = 1 - (1 - x) ^ power`
+    OutPowi(i32),
+    /// Combination of [`InPowi`](AudioEasing::InPowi) and [`OutPowi`](AudioEasing::OutPowi)
+    InOutPowi(i32),
+    /// `f(x) = x ^ power`
+    InPowf(f64),
+    /// `f(x) = 1 - (1 - x) ^ power`
+    OutPowf(f64),
+    /// Combination of [`InPowf`](AudioEasing::InPowf) and [`OutPowf`](AudioEasing::OutPowf)
+    InOutPowf(f64),
+}
+
+impl Default for AudioEasing {
+    fn default() -> Self {
+        AudioEasing::Linear
+    }
+}
+
+impl From<AudioEasing> for Easing {
+    fn from(easing: AudioEasing) -> Self {
+        match easing {
+            AudioEasing::Linear => Easing::Linear,
+            AudioEasing::InPowi(power) => Easing::InPowi(power),
+            AudioEasing::OutPowi(power) => Easing::OutPowi(power),
+            AudioEasing::InOutPowi(power) => Easing::InOutPowi(power),
+            AudioEasing::InPowf(power) => Easing::InPowf(power),
+            AudioEasing::OutPowf(power) => Easing::OutPowf(power),
+            AudioEasing::InOutPowf(power) => Easing::InOutPowf(power),
+        }
+    }
+}
+
+/// A queued channel command that can be given a custom [`AudioTween`]
+///
+/// The command is added to the channel when this value is dropped.
+/// If no tween is configured, the [default tween](AudioTween::default) is used.
+///
+/// ```edition2018
+/// # use bevy::prelude::*;
+/// # use bevy_kira_audio::{Audio, AudioTween};
+/// # use std::time::Duration;
+///
+/// fn my_system(audio: Res<Audio>) {
+///     audio.stop().with_tween(AudioTween::linear(Duration::from_secs(2)));
+/// }
+/// ```
+pub struct TweenCommand<'a> {
+    command: Option<AudioCommand>,
+    queue: &'a RwLock<VecDeque<AudioCommand>>,
+}
+
+impl<'a> TweenCommand<'a> {
+    pub(crate) fn new(command: AudioCommand, queue: &'a RwLock<VecDeque<AudioCommand>>) -> Self {
+        TweenCommand {
+            command: Some(command),
+            queue,
+        }
+    }
+
+    /// Use the given tween for this command
+    pub fn with_tween(mut self, tween: AudioTween) -> Self {
+        if let Some(command_tween) = self
+            .command
+            .as_mut()
+            .and_then(|command| command.tween_mut())
+        {
+            *command_tween = tween;
+        }
+        self
+    }
+}
+
+impl<'a> Drop for TweenCommand<'a> {
+    fn drop(&mut self) {
+        if let Some(command) = self.command.take() {
+            self.queue.write().push_front(command);
+        }
+    }
+}